@@ -0,0 +1,177 @@
+use std::fs;
+
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::float::{Float, Precision};
+use crate::model_file::{ModelError, ModelFile};
+use crate::training::{TrainingHistory, TrainingState};
+use crate::MLP;
+
+/// Punkt kontrolny: model z ustawieniami uczenia, stan generatora losowego i stan uczenia.
+#[derive(Serialize, Deserialize)]
+struct Checkpoint<F> {
+    model: ModelFile,
+    rng: ChaCha8Rng,
+    state: TrainingState<F>,
+}
+
+impl<F: Float> MLP<F> {
+    /// Zapisuje punkt kontrolny uczenia do pliku JSON.
+    ///
+    /// Plik jest najpierw zapisywany pod tymczasową nazwą i dopiero potem podmieniany,
+    /// więc przerwanie w trakcie zapisu nie niszczy poprzedniego punktu kontrolnego.
+    ///
+    /// # Argumenty
+    ///
+    /// * `state` - Stan uczenia.
+    /// * `path` - Ścieżka pliku punktu kontrolnego.
+    pub(crate) fn save_checkpoint(&self, state: &TrainingState<F>, path: &str) -> Result<(), ModelError> {
+        let checkpoint = Checkpoint {
+            model: self.to_model_file(),
+            rng: self.rng.clone(),
+            state: state.clone(),
+        };
+        let temp_path = format!("{}.tmp", path);
+        fs::write(&temp_path, serde_json::to_string(&checkpoint)?)?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// Sprawdza, czy punkt kontrolny dotyczy sieci o tej samej architekturze i tych samych
+    /// parametrach uczenia (liczba kroków, współczynnik uczenia, ziarno, precyzja) co `self`.
+    ///
+    /// # Argumenty
+    ///
+    /// * `path` - Ścieżka pliku punktu kontrolnego.
+    pub(crate) fn check_checkpoint(&self, path: &str) -> Result<(), ModelError> {
+        let checkpoint: Checkpoint<F> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let precision = checkpoint.model.precision();
+        let saved = MLP::<F>::from_model_file(checkpoint.model)?;
+        let describe = |mlp: &MLP<F>, precision: Precision| {
+            format!(
+                "{}-{}-{}, {} kroków, współczynnik uczenia {}, ziarno {}, precyzja {}",
+                mlp.n_inputs(),
+                mlp.N,
+                mlp.n_outputs(),
+                mlp.learning_steps,
+                mlp.learning_rate,
+                mlp.seed,
+                precision.name()
+            )
+        };
+        let (found, expected) = (describe(&saved, precision), describe(self, F::PRECISION));
+        if found != expected {
+            return Err(ModelError::Mismatch(format!(
+                "{} zawiera sieć {}, a żądano {}",
+                path, found, expected
+            )));
+        }
+        Ok(())
+    }
+
+    /// Wznawia uczenie z punktu kontrolnego zapisanego przez `MLP::fit_outputs`.
+    ///
+    /// Dla tych samych danych i tej samej precyzji `F` wynik jest identyczny z nieprzerwanym
+    /// uczeniem.
+    ///
+    /// # Argumenty
+    ///
+    /// * `path` - Ścieżka pliku punktu kontrolnego.
+    /// * `data` - Te same dane wejściowe, które przekazano do przerwanego uczenia.
+    /// * `targets` - Te same wektory docelowe.
+    ///
+    /// # Zwraca
+    ///
+    /// Nauczony model i pełną historię uczenia albo błąd odczytu punktu kontrolnego.
+    pub(crate) fn resume(
+        path: &str,
+        data: &[Vec<F>],
+        targets: &[Vec<F>],
+    ) -> Result<(MLP<F>, TrainingHistory), ModelError> {
+        let checkpoint: Checkpoint<F> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if checkpoint.state.indices.len() != data.len() || targets.len() != data.len() {
+            return Err(ModelError::Shape(format!(
+                "punkt kontrolny dotyczy {} próbek, podano {} próbek i {} wartości docelowych",
+                checkpoint.state.indices.len(),
+                data.len(),
+                targets.len()
+            )));
+        }
+
+        let mut mlp = MLP::from_model_file(checkpoint.model)?;
+        // `dot` obcina dłuższy wektor, więc dane o innej szerokości uczyłyby sieć po cichu
+        if let Some(row) = data.iter().find(|row| row.len() != mlp.n_inputs()) {
+            return Err(ModelError::Mismatch(format!(
+                "sieć ma {} wejść, a wiersz danych {} wartości",
+                mlp.n_inputs(),
+                row.len()
+            )));
+        }
+        if let Some(row) = targets.iter().find(|row| row.len() != mlp.n_outputs()) {
+            return Err(ModelError::Mismatch(format!(
+                "sieć ma {} wyjść, a wektor docelowy {} wartości",
+                mlp.n_outputs(),
+                row.len()
+            )));
+        }
+        mlp.rng = checkpoint.rng;
+        let history = mlp.train(data, targets, checkpoint.state);
+        Ok((mlp, history))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::Schedule;
+    use crate::{generate_data, generate_training_data, MODEL_RNG_SEED, RNG_SEED};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_resumed_run_matches_uninterrupted() {
+        let data = generate_data(100, 0.0, PI, RNG_SEED);
+        let targets: Vec<Vec<f64>> = generate_training_data(&data).into_iter().map(|t| vec![t]).collect();
+        let path = std::env::temp_dir()
+            .join(format!("mlp_checkpoint_{}.json", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+
+        let build = || {
+            let mut mlp = MLP::new(2, 6, 1, 1000, 0.1, MODEL_RNG_SEED);
+            mlp.config.validation_split = 0.2;
+            mlp.config.eval_every = 50;
+            mlp.config.batch_size = 4;
+            mlp.config.dropout = 0.1;
+            mlp.config.schedule = Schedule::ReduceOnPlateau { factor: 0.5, patience: 1, min_lr: 0.01 };
+            mlp
+        };
+
+        let mut uninterrupted = build();
+        let expected_history = uninterrupted.fit_outputs(&data, &targets);
+
+        // Ostatni punkt kontrolny pochodzi z kroku 700; dalsze kroki "przerwanego" uczenia są pomijane
+        let mut interrupted = build();
+        interrupted.config.checkpoint_every = Some(350);
+        interrupted.config.checkpoint_path = path.clone();
+        interrupted.fit_outputs(&data, &targets);
+
+        let (resumed, history) = MLP::resume(&path, &data, &targets).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(resumed.parameters(), uninterrupted.parameters());
+        assert_eq!(history.steps, expected_history.steps);
+        assert_eq!(history.validation_loss, expected_history.validation_loss);
+
+        // Punkt kontrolny innej sieci albo dane o innej szerokości są odrzucane
+        interrupted.fit_outputs(&data, &targets);
+        assert!(build().check_checkpoint(&path).is_ok());
+        let other = MLP::<f64>::new(2, 6, 1, 2000, 0.1, MODEL_RNG_SEED);
+        assert!(matches!(other.check_checkpoint(&path), Err(ModelError::Mismatch(_))));
+        let single = MLP::<f32>::new(2, 6, 1, 1000, 0.1, MODEL_RNG_SEED);
+        assert!(matches!(single.check_checkpoint(&path), Err(ModelError::Mismatch(_))));
+        let wide: Vec<Vec<f64>> = data.iter().map(|x| vec![x[0], x[1], 0.0]).collect();
+        assert!(matches!(MLP::resume(&path, &wide, &targets), Err(ModelError::Mismatch(_))));
+        fs::remove_file(&path).unwrap();
+    }
+}
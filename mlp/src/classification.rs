@@ -0,0 +1,203 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::loss::Loss;
+use crate::scaling::Scaling;
+use crate::{OutputActivation, MLP, MODEL_RNG_SEED, RNG_SEED};
+
+const WINE_DATA_PATH: &str = "../nbc/wine.data";
+
+/// Macierz pomyłek: wiersze to klasy rzeczywiste, kolumny to klasy przewidziane.
+struct ConfusionMatrix {
+    counts: Vec<Vec<usize>>,
+}
+
+impl ConfusionMatrix {
+    /// Tworzy macierz pomyłek dla `n_classes` klas.
+    ///
+    /// # Argumenty
+    ///
+    /// * `predicted` - Przewidziane indeksy klas.
+    /// * `actual` - Rzeczywiste indeksy klas.
+    /// * `n_classes` - Liczba klas.
+    fn new(predicted: &[usize], actual: &[usize], n_classes: usize) -> Self {
+        let mut counts = vec![vec![0; n_classes]; n_classes];
+        for (&p, &a) in predicted.iter().zip(actual.iter()) {
+            counts[a][p] += 1;
+        }
+        ConfusionMatrix { counts }
+    }
+
+    /// Dokładność, czyli udział poprawnie sklasyfikowanych próbek.
+    fn accuracy(&self) -> f64 {
+        let correct: usize = (0..self.counts.len()).map(|i| self.counts[i][i]).sum();
+        let all: usize = self.counts.iter().flatten().sum();
+        correct as f64 / all as f64
+    }
+}
+
+impl fmt::Display for ConfusionMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Macierz pomyłek (wiersze - klasa rzeczywista, kolumny - przewidziana):")?;
+        for row in self.counts.iter() {
+            let row: Vec<String> = row.iter().map(|c| format!("{:>4}", c)).collect();
+            writeln!(f, "{}", row.join(""))?;
+        }
+        write!(f, "Dokładność: {:.2}%", self.accuracy() * 100.0)
+    }
+}
+
+/// Wczytuje zbiór danych, w którym pierwsza kolumna jest etykietą klasy (format `wine.data`).
+///
+/// # Zwraca
+///
+/// Krotka zawierająca etykiety klas oraz wektory cech albo błąd odczytu, także dla
+/// wiersza z wartością, która nie jest liczbą.
+fn load_labeled_data(path: &str, delimiter: &str) -> io::Result<(Vec<usize>, Vec<Vec<f64>>)> {
+    let mut labels: Vec<usize> = Vec::new();
+    let mut data: Vec<Vec<f64>> = Vec::new();
+
+    let reader = BufReader::new(File::open(path)?);
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("{}: niepoprawny wiersz {}", path, i + 1));
+        let mut values = line.split(delimiter);
+        labels.push(values.next().unwrap_or("").trim().parse().map_err(|_| invalid())?);
+        data.push(values.map(|v| v.trim().parse().map_err(|_| invalid())).collect::<io::Result<_>>()?);
+    }
+
+    Ok((labels, data))
+}
+
+/// Zamienia dowolne etykiety na kolejne indeksy klas `0..n_classes`.
+///
+/// # Zwraca
+///
+/// Krotka zawierająca indeksy klas oraz posortowaną listę oryginalnych etykiet.
+fn index_labels(labels: &[usize]) -> (Vec<usize>, Vec<usize>) {
+    let mut classes: Vec<usize> = labels.to_vec();
+    classes.sort_unstable();
+    classes.dedup();
+    let indices = labels
+        .iter()
+        .map(|l| classes.binary_search(l).unwrap())
+        .collect();
+    (indices, classes)
+}
+
+/// Koduje indeksy klas w postaci "one-hot".
+fn one_hot(labels: &[usize], n_classes: usize) -> Vec<Vec<f64>> {
+    labels
+        .iter()
+        .map(|&label| {
+            let mut row = vec![0.0; n_classes];
+            row[label] = 1.0;
+            row
+        })
+        .collect()
+}
+
+/// Koduje indeksy klas jako wektory docelowe dla sieci o `n_outputs` wyjściach.
+///
+/// Dla jednego wyjścia (klasyfikacja binarna z aktywacją `Sigmoid`) etykieta jest
+/// wartością `0.0` albo `1.0`, dla wielu wyjść stosowane jest kodowanie "one-hot".
+pub(crate) fn encode_labels(labels: &[usize], n_outputs: usize) -> Vec<Vec<f64>> {
+    if n_outputs == 1 {
+        labels.iter().map(|&label| vec![label as f64]).collect()
+    } else {
+        one_hot(labels, n_outputs)
+    }
+}
+
+/// Dzieli dane na zbiór uczący i testowy po przetasowaniu ziarnem `seed`.
+///
+/// # Zwraca
+///
+/// Krotka `(train_labels, test_labels, train_data, test_data)`.
+#[allow(clippy::type_complexity)]
+fn train_test_split(
+    data: &[Vec<f64>],
+    labels: &[usize],
+    test_size: f64,
+    seed: u64,
+) -> (Vec<usize>, Vec<usize>, Vec<Vec<f64>>, Vec<Vec<f64>>) {
+    let mut indices: Vec<usize> = (0..data.len()).collect();
+    indices.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+    let test_len = (test_size * data.len() as f64) as usize;
+    let (test, train) = indices.split_at(test_len);
+
+    (
+        train.iter().map(|&i| labels[i]).collect(),
+        test.iter().map(|&i| labels[i]).collect(),
+        train.iter().map(|&i| data[i].clone()).collect(),
+        test.iter().map(|&i| data[i].clone()).collect(),
+    )
+}
+
+/// Uczy klasyfikator MLP na zbiorze `wine.data` i wypisuje wyniki na zbiorze testowym.
+pub(crate) fn classify_wine() -> io::Result<()> {
+    let (labels, data) = load_labeled_data(WINE_DATA_PATH, ",")?;
+    let (labels, classes) = index_labels(&labels);
+    let n_classes = classes.len();
+
+    let (train_labels, test_labels, train_data, test_data) = train_test_split(&data, &labels, 0.3, RNG_SEED);
+
+    let mut mlp = MLP::new(train_data[0].len(), 10, n_classes, 20_000, 0.05, MODEL_RNG_SEED);
+    // Standaryzacja cech na podstawie statystyk zbioru uczącego
+    mlp.config.input_scaling = Scaling::Standard;
+    mlp.output_activation = OutputActivation::Softmax;
+    mlp.loss = Loss::CategoricalCrossEntropy;
+    mlp.fit_outputs(&train_data, &encode_labels(&train_labels, n_classes));
+
+    let test_targets = encode_labels(&test_labels, n_classes);
+    let loss = mlp.loss.mean_outputs(&mlp.predict_proba(&test_data), &test_targets);
+    let confusion = ConfusionMatrix::new(&mlp.predict_classes(&test_data), &test_labels, n_classes);
+
+    println!("Klasy: {:?}", classes);
+    println!("Entropia krzyżowa (zbiór testowy): {:.4}", loss);
+    println!("{}", confusion);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classifier_separates_clusters() {
+        // Dwie dobrze rozdzielone grupy punktów
+        let data: Vec<Vec<f64>> = (0..40)
+            .map(|i| {
+                let offset = if i % 2 == 0 { -2.0 } else { 2.0 };
+                vec![offset + (i as f64 * 0.37).sin() * 0.5, offset + (i as f64 * 0.73).cos() * 0.5]
+            })
+            .collect();
+        let labels: Vec<usize> = (0..40).map(|i| i % 2).collect();
+
+        let heads = [
+            (OutputActivation::Sigmoid, Loss::BinaryCrossEntropy, 1),
+            (OutputActivation::Softmax, Loss::CategoricalCrossEntropy, 2),
+        ];
+        for (activation, loss, n_outputs) in heads {
+            let mut mlp = MLP::new(2, 4, n_outputs, 2000, 0.1, MODEL_RNG_SEED);
+            mlp.output_activation = activation;
+            mlp.loss = loss;
+            let targets = encode_labels(&labels, n_outputs);
+            mlp.fit_outputs(&data, &targets);
+
+            let confusion = ConfusionMatrix::new(&mlp.predict_classes(&data), &labels, 2);
+            assert_eq!(confusion.accuracy(), 1.0);
+
+            let loss = mlp.loss.mean_outputs(&mlp.predict_proba(&data), &targets);
+            assert!(loss < 0.2, "loss = {}", loss);
+        }
+    }
+}
@@ -0,0 +1,832 @@
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use csv::Writer;
+
+use crate::classification;
+use crate::cross_validation::Metrics;
+use crate::dataset::{Dataset, DatasetError};
+use crate::ensemble;
+use crate::evolution::{self, GradientFree, SearchTrace};
+use crate::float::{self, cast, cast_rows, Float, Precision};
+use crate::functions::{self, Problem, TargetFunction};
+use crate::line_search::{self, LineSearchMethod};
+use crate::model_file::{read_model_file, ModelError};
+use crate::scaling::Scaling;
+use crate::schedule::Schedule;
+use crate::telemetry::TelemetryFormat;
+use crate::throughput;
+use crate::{
+    benchmark, compare_regularization, generate_plot_csv, random_search, BENCHMARK_SAMPLES, MLP, MODEL_RNG_SEED, RNG_SEED,
+};
+
+/// Wielowarstwowy perceptron z jedną warstwą ukrytą: uczenie, predykcja, ocena i porównania.
+#[derive(Parser, Debug)]
+#[command(name = "mlp", version)]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Uczy sieć i zapisuje model oraz historię uczenia.
+    Train(Box<TrainArgs>),
+    /// Wczytuje model i zapisuje predykcje dla podanych danych.
+    Predict(PredictArgs),
+    /// Wczytuje model i wypisuje miary jakości na podanych danych.
+    Evaluate(EvaluateArgs),
+    /// Uruchamia jedno z porównań konfiguracji.
+    Benchmark(BenchmarkArgs),
+    /// Uczy sieć na cos(x*y)*cos(2x) i zapisuje pliki CSV, wykresy oraz mapy neuronów ukrytych (HTML).
+    Plot(PlotArgs),
+}
+
+/// Źródło danych: plik CSV z nagłówkiem albo funkcja z rejestru `functions::TARGET_FUNCTIONS`.
+#[derive(Args, Debug)]
+struct DataArgs {
+    /// Plik CSV z nagłówkiem; bez niego dane są generowane z funkcji `--function`.
+    #[arg(long)]
+    data: Option<String>,
+    /// Kolumny wejściowe pliku CSV, oddzielone przecinkami.
+    #[arg(long, value_delimiter = ',', requires = "data")]
+    features: Vec<String>,
+    /// Kolumna z wartością docelową w pliku CSV.
+    #[arg(long, requires = "data")]
+    target: Option<String>,
+    /// Nazwa funkcji docelowej.
+    #[arg(long, default_value = "cos", conflicts_with = "data")]
+    function: String,
+    /// Wymiar wejścia funkcji.
+    #[arg(long, default_value_t = 2)]
+    dim: usize,
+    /// Dolna granica dziedziny każdej współrzędnej; domyślnie zależna od funkcji.
+    #[arg(long, conflicts_with = "data", allow_negative_numbers = true)]
+    min: Option<f64>,
+    /// Górna granica dziedziny każdej współrzędnej; domyślnie zależna od funkcji.
+    #[arg(long, conflicts_with = "data", allow_negative_numbers = true)]
+    max: Option<f64>,
+    /// Odchylenie standardowe szumu dodawanego do wartości funkcji.
+    #[arg(long, default_value_t = 0.0)]
+    noise: f64,
+    /// Liczba generowanych próbek.
+    #[arg(long, default_value_t = 1000)]
+    samples: usize,
+    /// Ziarno generatora danych.
+    #[arg(long, default_value_t = RNG_SEED)]
+    data_seed: u64,
+}
+
+/// Algorytm optymalizacji wag; dla metod bezgradientowych `--steps` to liczba ewaluacji straty,
+/// a dla metod z przeszukiwaniem liniowym - maksymalna liczba iteracji.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Optimizer {
+    /// Stochastyczny spadek wzdłuż gradientu (wsadowy przy `--batch-size` > 1).
+    Sgd,
+    /// Algorytm genetyczny z kodowaniem rzeczywistym.
+    Ga,
+    /// Ewolucja różnicowa.
+    De,
+    /// Optymalizacja rojem cząstek.
+    Pso,
+    /// CMA-ES.
+    CmaEs,
+    /// L-BFGS na pełnym zbiorze uczącym.
+    Lbfgs,
+    /// Nieliniowe gradienty sprzężone na pełnym zbiorze uczącym.
+    Cg,
+}
+
+impl Optimizer {
+    /// Odpowiadająca metoda bezgradientowa; `None` dla metod gradientowych.
+    fn gradient_free(self) -> Option<GradientFree> {
+        match self {
+            Optimizer::Sgd | Optimizer::Lbfgs | Optimizer::Cg => None,
+            Optimizer::Ga => Some(GradientFree::Genetic),
+            Optimizer::De => Some(GradientFree::DifferentialEvolution),
+            Optimizer::Pso => Some(GradientFree::ParticleSwarm),
+            Optimizer::CmaEs => Some(GradientFree::CmaEs),
+        }
+    }
+
+    /// Odpowiadająca metoda z przeszukiwaniem liniowym.
+    fn line_search(self) -> Option<LineSearchMethod> {
+        match self {
+            Optimizer::Lbfgs => Some(LineSearchMethod::Lbfgs),
+            Optimizer::Cg => Some(LineSearchMethod::ConjugateGradient),
+            _ => None,
+        }
+    }
+}
+
+/// Harmonogram współczynnika uczenia; parametry są dobierane do liczby kroków.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum ScheduleKind {
+    Constant,
+    Step,
+    Exponential,
+    Cosine,
+    Plateau,
+}
+
+#[derive(Args, Debug)]
+struct TrainArgs {
+    #[command(flatten)]
+    data: DataArgs,
+    /// Liczba neuronów w warstwie ukrytej.
+    #[arg(long, default_value_t = 20)]
+    hidden: usize,
+    /// Liczba kroków uczenia.
+    #[arg(long, default_value_t = 100_000)]
+    steps: usize,
+    /// Współczynnik uczenia.
+    #[arg(long, default_value_t = 0.1, allow_negative_numbers = true)]
+    learning_rate: f64,
+    /// Ziarno generatora sieci.
+    #[arg(long, default_value_t = MODEL_RNG_SEED)]
+    seed: u64,
+    /// Precyzja wag i obliczeń sieci; zapisywana w pliku modelu.
+    #[arg(long, value_enum, default_value_t = Precision::F64)]
+    precision: Precision,
+    #[arg(long, value_enum, default_value_t = Optimizer::Sgd)]
+    optimizer: Optimizer,
+    /// Liczba próbek w jednym kroku uczenia.
+    #[arg(long, default_value_t = 1)]
+    batch_size: usize,
+    #[arg(long, value_enum, default_value_t = ScheduleKind::Constant)]
+    schedule: ScheduleKind,
+    /// Liczba kroków liniowego rozgrzewania.
+    #[arg(long, default_value_t = 0)]
+    warmup_steps: usize,
+    /// Współczynnik kary L1 za wagi.
+    #[arg(long, default_value_t = 0.0)]
+    l1: f64,
+    /// Współczynnik kary L2 za wagi.
+    #[arg(long, default_value_t = 0.0)]
+    l2: f64,
+    /// Prawdopodobieństwo wyłączenia neuronu ukrytego.
+    #[arg(long, default_value_t = 0.0)]
+    dropout: f64,
+    /// Maksymalna norma gradientu.
+    #[arg(long, allow_negative_numbers = true)]
+    clip_norm: Option<f64>,
+    /// Równoległe obliczenia na wierszach wsadu.
+    #[arg(long)]
+    parallel: bool,
+    /// Część danych odkładana do walidacji.
+    #[arg(long, default_value_t = 0.0)]
+    validation_split: f64,
+    /// Liczba ocen bez poprawy straty walidacyjnej przed zatrzymaniem.
+    #[arg(long)]
+    patience: Option<usize>,
+    /// Co ile kroków liczona jest strata.
+    #[arg(long, default_value_t = 1000)]
+    eval_every: usize,
+    /// Skalowanie wejść; domyślnie `standard` dla pliku CSV i `none` dla funkcji.
+    #[arg(long, value_enum)]
+    input_scaling: Option<Scaling>,
+    /// Skalowanie wartości docelowych; domyślnie jak dla wejść.
+    #[arg(long, value_enum)]
+    target_scaling: Option<Scaling>,
+    /// Co ile kroków zapisywany jest punkt kontrolny.
+    #[arg(long)]
+    checkpoint_every: Option<usize>,
+    /// Ścieżka pliku punktu kontrolnego.
+    #[arg(long, default_value = "checkpoint.json")]
+    checkpoint: String,
+    /// Wznawia uczenie z pliku `--checkpoint` zamiast zaczynać od nowa.
+    #[arg(long)]
+    resume: bool,
+    /// Co ile kroków zapisywana jest telemetria (strata, norma gradientu, normy wag, współczynnik uczenia).
+    #[arg(long)]
+    telemetry_every: Option<usize>,
+    /// Ścieżka pliku telemetrii.
+    #[arg(long, default_value = "telemetry.jsonl")]
+    telemetry: String,
+    #[arg(long, value_enum, default_value_t = TelemetryFormat::JsonLines)]
+    telemetry_format: TelemetryFormat,
+    /// Pasek postępu z szacowanym czasem do końca.
+    #[arg(long)]
+    progress: bool,
+    /// Ścieżka zapisu modelu.
+    #[arg(long, default_value = "model.json")]
+    model: String,
+    /// Ścieżka zapisu historii uczenia.
+    #[arg(long, default_value = "history.csv")]
+    history: String,
+}
+
+#[derive(Args, Debug)]
+struct PredictArgs {
+    #[command(flatten)]
+    data: DataArgs,
+    /// Plik modelu zapisany przez `train`.
+    #[arg(long, default_value = "model.json")]
+    model: String,
+    /// Plik CSV z wejściami i predykcjami.
+    #[arg(long, default_value = "predictions.csv")]
+    output: String,
+}
+
+#[derive(Args, Debug)]
+struct EvaluateArgs {
+    #[command(flatten)]
+    data: DataArgs,
+    /// Plik modelu zapisany przez `train`.
+    #[arg(long, default_value = "model.json")]
+    model: String,
+}
+
+/// Rodzaj porównania uruchamianego przez `benchmark`.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum BenchmarkKind {
+    /// Siatka hiperparametrów (z `--folds` oceniana walidacją krzyżową).
+    Grid,
+    /// Losowe przeszukiwanie hiperparametrów.
+    Random,
+    /// Przepustowość predykcji i uczenia.
+    Throughput,
+    /// Wszystkie funkcje z rejestru.
+    Functions,
+    /// Warianty regularyzacji.
+    Regularization,
+    /// Klasyfikacja zbioru wine.data.
+    Wine,
+    /// Metody bezgradientowe a propagacja wsteczna przy równej liczbie ewaluacji.
+    GradientFree,
+    /// L-BFGS i gradienty sprzężone a SGD.
+    LineSearch,
+    /// Zespoły sieci (różne ziarna i bagging) z mapami niepewności.
+    Ensemble,
+    /// Uczenie w pojedynczej i podwójnej precyzji.
+    Precision,
+}
+
+#[derive(Args, Debug)]
+struct BenchmarkArgs {
+    #[arg(long, value_enum, default_value_t = BenchmarkKind::Grid)]
+    kind: BenchmarkKind,
+    /// Liczba podziałów walidacji krzyżowej (tylko `grid`).
+    #[arg(long)]
+    folds: Option<usize>,
+    /// Liczba losowanych konfiguracji (tylko `random`).
+    #[arg(long, default_value_t = 50)]
+    count: usize,
+    /// Odchylenie standardowe szumu (tylko `functions`).
+    #[arg(long, default_value_t = 0.05)]
+    noise: f64,
+    /// Liczba ewaluacji straty dla każdej metody (tylko `gradient-free`).
+    #[arg(long, default_value_t = 20_000)]
+    evaluations: usize,
+    /// Maksymalna liczba iteracji L-BFGS i gradientów sprzężonych (tylko `line-search`).
+    #[arg(long, default_value_t = 1000)]
+    iterations: usize,
+    /// Liczba członków każdego zespołu (tylko `ensemble`).
+    #[arg(long, default_value_t = 8)]
+    members: usize,
+    /// Ziarno bazowe sieci.
+    #[arg(long, default_value_t = MODEL_RNG_SEED)]
+    seed: u64,
+    /// Plik CSV z wynikami (`grid`, `random`, `gradient-free`, `line-search` i `precision`) lub katalog
+    /// map niepewności (`ensemble`); domyślnie nazwa zależna od rodzaju.
+    #[arg(long)]
+    output: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct PlotArgs {
+    /// Liczba neuronów w warstwie ukrytej.
+    #[arg(long, default_value_t = 20)]
+    hidden: usize,
+    /// Liczba kroków uczenia.
+    #[arg(long, default_value_t = 1_000_000)]
+    steps: usize,
+    /// Współczynnik uczenia.
+    #[arg(long, default_value_t = 0.1)]
+    learning_rate: f64,
+    /// Ziarno generatora sieci.
+    #[arg(long, default_value_t = MODEL_RNG_SEED)]
+    seed: u64,
+    /// Katalog plików wynikowych.
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+    /// Wznawia przerwane uczenie z `checkpoint.json` w katalogu wynikowym; punkt kontrolny
+    /// musi dotyczyć sieci o tych samych parametrach.
+    #[arg(long)]
+    resume: bool,
+}
+
+/// Błąd wykonania polecenia; każdy rodzaj ma własny kod wyjścia.
+#[derive(Debug)]
+pub(crate) enum CliError {
+    /// Niepoprawne lub niedostępne dane wejściowe.
+    Data(String),
+    /// Błąd odczytu, zapisu lub zgodności modelu.
+    Model(ModelError),
+    /// Błąd zapisu wyników.
+    Output(String),
+}
+
+impl CliError {
+    /// Kod wyjścia procesu; kod 2 (błędne argumenty) zwraca sam parser.
+    pub(crate) fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            CliError::Data(_) => 3,
+            CliError::Model(_) => 4,
+            CliError::Output(_) => 5,
+        })
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Data(msg) => write!(f, "niepoprawne dane: {}", msg),
+            CliError::Model(e) => write!(f, "{}", e),
+            CliError::Output(msg) => write!(f, "nie udało się zapisać wyników: {}", msg),
+        }
+    }
+}
+
+impl From<ModelError> for CliError {
+    fn from(e: ModelError) -> Self {
+        CliError::Model(e)
+    }
+}
+
+/// Zamienia dowolny błąd zapisu na `CliError::Output`.
+fn output_error(e: impl fmt::Display) -> CliError {
+    CliError::Output(e.to_string())
+}
+
+/// Zamienia błąd dopasowania modelu na `CliError::Model`, a pozostałe błędy na `CliError::Output`.
+fn model_or_output_error(e: Box<dyn std::error::Error>) -> CliError {
+    match e.downcast::<ModelError>() {
+        Ok(e) => CliError::Model(*e),
+        Err(e) => output_error(e),
+    }
+}
+
+/// Wczytane dane: wejścia, opcjonalne wartości docelowe i domyślne skalowanie dla ich źródła.
+struct LoadedData {
+    features: Vec<Vec<f64>>,
+    targets: Option<Vec<f64>>,
+    scaling: Scaling,
+}
+
+impl DataArgs {
+    /// Wczytuje plik CSV albo generuje próbki funkcji.
+    fn load(&self) -> Result<LoadedData, CliError> {
+        if let Some(path) = &self.data {
+            if self.features.is_empty() {
+                return Err(CliError::Data("podaj kolumny wejściowe w --features".to_string()));
+            }
+            let features: Vec<&str> = self.features.iter().map(String::as_str).collect();
+            let targets: Vec<&str> = self.target.iter().map(String::as_str).collect();
+            let dataset = Dataset::from_csv(path, &features, &targets)
+                .map_err(|e: DatasetError| CliError::Data(format!("{}: {}", path, e)))?;
+            return LoadedData {
+                targets: self.target.as_ref().map(|_| dataset.target_values()),
+                features: dataset.features,
+                // Kolumny z pliku mogą mieć dowolne zakresy wartości
+                scaling: Scaling::Standard,
+            }
+            .non_empty();
+        }
+
+        let function = TargetFunction::from_name(&self.function)
+            .ok_or_else(|| CliError::Data(format!("nieznana funkcja '{}'", self.function)))?;
+        let mut problem = Problem::new(function, self.dim).map_err(CliError::Data)?;
+        if !(self.noise >= 0.0 && self.noise.is_finite()) {
+            return Err(CliError::Data(format!("niepoprawny poziom szumu {}", self.noise)));
+        }
+        problem.noise = self.noise;
+        problem.min = self.min.unwrap_or(problem.min);
+        problem.max = self.max.unwrap_or(problem.max);
+        if !(problem.min < problem.max && problem.min.is_finite() && problem.max.is_finite()) {
+            return Err(CliError::Data(format!(
+                "niepoprawna dziedzina [{}, {}]",
+                problem.min, problem.max
+            )));
+        }
+        let (features, targets) = problem.generate(self.samples, self.data_seed);
+        LoadedData {
+            features,
+            targets: Some(targets),
+            scaling: Scaling::None,
+        }
+        .non_empty()
+    }
+}
+
+impl LoadedData {
+    /// Zwraca dane albo błąd, gdy nie ma żadnej próbki.
+    fn non_empty(self) -> Result<LoadedData, CliError> {
+        if self.features.is_empty() {
+            return Err(CliError::Data("brak próbek danych".to_string()));
+        }
+        Ok(self)
+    }
+
+    /// Wartości docelowe albo błąd, gdy plik CSV podano bez `--target`.
+    fn require_targets(&self) -> Result<&[f64], CliError> {
+        self.targets
+            .as_deref()
+            .ok_or_else(|| CliError::Data("podaj kolumnę docelową w --target".to_string()))
+    }
+}
+
+impl ScheduleKind {
+    /// Harmonogram z parametrami dobranymi do liczby kroków i współczynnika uczenia.
+    fn schedule(self, steps: usize, learning_rate: f64) -> Schedule {
+        match self {
+            ScheduleKind::Constant => Schedule::Constant,
+            ScheduleKind::Step => Schedule::StepDecay {
+                step_size: (steps / 4).max(1),
+                gamma: 0.5,
+            },
+            ScheduleKind::Exponential => Schedule::ExponentialDecay {
+                decay_steps: steps.max(1),
+                gamma: 0.1,
+            },
+            ScheduleKind::Cosine => Schedule::CosineWarmRestarts {
+                period: (steps / 4).max(1),
+                period_mult: 1,
+                min_lr: 0.01 * learning_rate,
+            },
+            ScheduleKind::Plateau => Schedule::ReduceOnPlateau {
+                factor: 0.5,
+                patience: 3,
+                min_lr: 0.01 * learning_rate,
+            },
+        }
+    }
+}
+
+/// Wykonuje wybrane polecenie.
+pub(crate) fn run(cli: Cli) -> Result<(), CliError> {
+    match cli.command {
+        Command::Train(args) => train(*args),
+        Command::Predict(args) => predict(args),
+        Command::Evaluate(args) => evaluate(args),
+        Command::Benchmark(args) => run_benchmark(args),
+        Command::Plot(args) => plot(args),
+    }
+}
+
+/// Sprawdza liczbę neuronów ukrytych i współczynnik uczenia wspólne dla `train` i `plot`.
+fn check_network(hidden: usize, learning_rate: f64) -> Result<(), CliError> {
+    if hidden == 0 {
+        return Err(CliError::Data("sieć potrzebuje co najmniej jednego neuronu ukrytego (--hidden)".to_string()));
+    }
+    if !(learning_rate.is_finite() && learning_rate > 0.0) {
+        return Err(CliError::Data(format!(
+            "współczynnik uczenia --learning-rate {} musi być dodatni i skończony",
+            learning_rate
+        )));
+    }
+    Ok(())
+}
+
+fn plot(args: PlotArgs) -> Result<(), CliError> {
+    check_network(args.hidden, args.learning_rate)?;
+    if args.steps == 0 {
+        return Err(CliError::Data("wykres wymaga co najmniej jednego kroku uczenia (--steps)".to_string()));
+    }
+    generate_plot_csv(
+        args.hidden,
+        args.steps,
+        args.learning_rate,
+        args.seed,
+        &args.output_dir,
+        args.resume,
+    )
+    .map_err(output_error)
+}
+
+fn train(args: TrainArgs) -> Result<(), CliError> {
+    let loaded = args.data.load()?;
+    let targets = loaded.require_targets()?;
+    if !(0.0..1.0).contains(&args.validation_split) {
+        return Err(CliError::Data(format!(
+            "część walidacyjna {} spoza przedziału [0, 1)",
+            args.validation_split
+        )));
+    }
+    check_network(args.hidden, args.learning_rate)?;
+    if !(0.0..1.0).contains(&args.dropout) {
+        return Err(CliError::Data(format!("dropout {} spoza przedziału [0, 1)", args.dropout)));
+    }
+    if let Some(clip_norm) = args.clip_norm.filter(|c| !(c.is_finite() && *c > 0.0)) {
+        return Err(CliError::Data(format!(
+            "próg normy gradientu --clip-norm {} musi być dodatni i skończony",
+            clip_norm
+        )));
+    }
+    if args.resume && args.optimizer != Optimizer::Sgd {
+        return Err(CliError::Data(format!(
+            "--resume działa tylko z --optimizer sgd, podano {}",
+            args.optimizer.to_possible_value().map_or_else(String::new, |v| v.get_name().to_string())
+        )));
+    }
+
+    match args.precision {
+        Precision::F32 => train_with::<f32>(&args, &loaded, targets),
+        Precision::F64 => train_with::<f64>(&args, &loaded, targets),
+    }
+}
+
+/// Uczy sieć w precyzji `F`; wejścia i wartości docelowe są zamieniane na `F` przed uczeniem.
+fn train_with<F: Float>(args: &TrainArgs, loaded: &LoadedData, targets: &[f64]) -> Result<(), CliError> {
+    let features: Vec<Vec<F>> = cast_rows(&loaded.features);
+    let wrapped: Vec<Vec<F>> = targets.iter().map(|&t| vec![F::of(t)]).collect();
+    let mut mlp = MLP::<F>::new(
+        loaded.features[0].len(),
+        args.hidden,
+        1,
+        args.steps,
+        args.learning_rate,
+        args.seed,
+    );
+    mlp.config.batch_size = args.batch_size.max(1);
+    mlp.config.schedule = args.schedule.schedule(args.steps, args.learning_rate);
+    mlp.config.warmup_steps = args.warmup_steps;
+    mlp.config.l1 = args.l1;
+    mlp.config.l2 = args.l2;
+    mlp.config.dropout = args.dropout;
+    mlp.config.clip_norm = args.clip_norm;
+    mlp.config.parallel = args.parallel;
+    mlp.config.validation_split = args.validation_split;
+    mlp.config.patience = args.patience;
+    mlp.config.eval_every = args.eval_every.max(1);
+    mlp.config.input_scaling = args.input_scaling.unwrap_or(loaded.scaling);
+    mlp.config.target_scaling = args.target_scaling.or(args.input_scaling).unwrap_or(loaded.scaling);
+    mlp.config.checkpoint_every = args.checkpoint_every;
+    mlp.config.checkpoint_path = args.checkpoint.clone();
+    mlp.config.telemetry_every = args.telemetry_every;
+    mlp.config.telemetry_path = args.telemetry.clone();
+    mlp.config.telemetry_format = args.telemetry_format;
+    mlp.config.progress = args.progress;
+
+    if let Some(method) = args.optimizer.gradient_free() {
+        let trace = mlp.fit_gradient_free(&features, &wrapped, method, args.steps);
+        let metrics = Metrics::compute(&cast(&mlp.predict(&features)), targets);
+        println!("Zbiór uczący ({} próbek, {} ewaluacji): {}", targets.len(), args.steps, metrics);
+
+        mlp.save(&args.model)?;
+        save_trace(&trace, &args.history).map_err(output_error)?;
+        println!("Zapisano model w {} i przebieg optymalizacji w {}", args.model, args.history);
+        return Ok(());
+    }
+
+    if let Some(method) = args.optimizer.line_search() {
+        let report = mlp.fit_line_search(&features, &wrapped, method, args.steps);
+        println!("{}: {}", method.name(), report);
+        let metrics = Metrics::compute(&cast(&mlp.predict(&features)), targets);
+        println!("Zbiór uczący ({} próbek): {}", targets.len(), metrics);
+
+        mlp.save(&args.model)?;
+        save_loss_history(&report.loss_history, &args.history).map_err(output_error)?;
+        println!("Zapisano model w {} i przebieg optymalizacji w {}", args.model, args.history);
+        return Ok(());
+    }
+
+    let history = if args.resume {
+        mlp.check_checkpoint(&args.checkpoint)?;
+        let (resumed, history) = MLP::<F>::resume(&args.checkpoint, &features, &wrapped)?;
+        mlp = resumed;
+        history
+    } else {
+        mlp.fit(&features, &cast(targets))
+    };
+
+    let metrics = Metrics::compute(&cast(&mlp.predict(&features)), targets);
+    println!("Zbiór uczący ({} próbek): {}", targets.len(), metrics);
+
+    mlp.save(&args.model)?;
+    history.save_csv(&args.history).map_err(output_error)?;
+    println!("Zapisano model w {} i historię uczenia w {}", args.model, args.history);
+    Ok(())
+}
+
+/// Zapisuje przebieg optymalizacji bezgradientowej do pliku CSV.
+fn save_trace(trace: &SearchTrace, path: &str) -> Result<(), csv::Error> {
+    let mut wtr = Writer::from_path(path)?;
+    wtr.write_record(["evaluations", "best_loss"])?;
+    for (count, loss) in trace.evaluations.iter().zip(trace.best_loss.iter()) {
+        wtr.write_record(&[count.to_string(), loss.to_string()])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Zapisuje stratę po kolejnych iteracjach metody z przeszukiwaniem liniowym do pliku CSV.
+fn save_loss_history(losses: &[f64], path: &str) -> Result<(), csv::Error> {
+    let mut wtr = Writer::from_path(path)?;
+    wtr.write_record(["iteration", "loss"])?;
+    for (iteration, loss) in losses.iter().enumerate() {
+        wtr.write_record(&[iteration.to_string(), loss.to_string()])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Wczytuje model i zwraca jego predykcje dla danych, liczone w precyzji zapisanej w pliku modelu.
+fn load_predictions(path: &str, loaded: &LoadedData) -> Result<Vec<f64>, CliError> {
+    let file = read_model_file(path)?;
+    match file.precision() {
+        Precision::F32 => predictions(MLP::<f32>::from_model_file(file)?, path, loaded),
+        Precision::F64 => predictions(MLP::<f64>::from_model_file(file)?, path, loaded),
+    }
+}
+
+/// Sprawdza, czy liczba wejść modelu zgadza się z danymi, i liczy predykcje.
+fn predictions<F: Float>(mlp: MLP<F>, path: &str, loaded: &LoadedData) -> Result<Vec<f64>, CliError> {
+    let columns = loaded.features[0].len();
+    if mlp.n_inputs() != columns {
+        return Err(CliError::Data(format!(
+            "model {} oczekuje {} wejść, dane mają {} kolumn",
+            path,
+            mlp.n_inputs(),
+            columns
+        )));
+    }
+    Ok(cast(&mlp.predict(&cast_rows(&loaded.features))))
+}
+
+fn predict(args: PredictArgs) -> Result<(), CliError> {
+    let loaded = args.data.load()?;
+    let prediction = load_predictions(&args.model, &loaded)?;
+
+    let mut wtr = Writer::from_path(&args.output).map_err(output_error)?;
+    for (x, y) in loaded.features.iter().zip(prediction.iter()) {
+        wtr.write_record(x.iter().chain(std::iter::once(y)).map(|v| v.to_string()))
+            .map_err(output_error)?;
+    }
+    wtr.flush().map_err(output_error)?;
+    println!("Zapisano {} predykcji w {}", prediction.len(), args.output);
+    Ok(())
+}
+
+fn evaluate(args: EvaluateArgs) -> Result<(), CliError> {
+    let loaded = args.data.load()?;
+    let targets = loaded.require_targets()?;
+    println!("{}", Metrics::compute(&load_predictions(&args.model, &loaded)?, targets));
+    Ok(())
+}
+
+fn run_benchmark(args: BenchmarkArgs) -> Result<(), CliError> {
+    if let Some(folds) = args.folds {
+        if !(2..=BENCHMARK_SAMPLES).contains(&folds) {
+            return Err(CliError::Data(format!(
+                "liczba podziałów --folds {} spoza przedziału [2, {}]",
+                folds, BENCHMARK_SAMPLES
+            )));
+        }
+    }
+    match args.kind {
+        BenchmarkKind::Grid => {
+            let default = if args.folds.is_some() { "benchmark_cv.csv" } else { "benchmark.csv" };
+            let path = args.output.as_deref().unwrap_or(default);
+            benchmark(args.folds, args.seed, path).map_err(model_or_output_error)
+        }
+        BenchmarkKind::Random => {
+            let path = args.output.as_deref().unwrap_or("random_search.csv");
+            random_search(args.count, args.seed, path).map_err(model_or_output_error)
+        }
+        BenchmarkKind::Throughput => {
+            throughput::throughput_benchmark();
+            Ok(())
+        }
+        BenchmarkKind::Functions => {
+            functions::compare_functions(args.noise);
+            Ok(())
+        }
+        BenchmarkKind::Regularization => compare_regularization().map_err(output_error),
+        BenchmarkKind::Wine => {
+            classification::classify_wine().map_err(|e| CliError::Data(format!("wine.data: {}", e)))
+        }
+        BenchmarkKind::GradientFree => {
+            let path = args.output.as_deref().unwrap_or("gradient_free.csv");
+            evolution::compare_gradient_free(args.evaluations, args.seed, path).map_err(output_error)
+        }
+        BenchmarkKind::LineSearch => {
+            let path = args.output.as_deref().unwrap_or("line_search.csv");
+            line_search::compare_line_search(args.iterations, args.seed, path).map_err(output_error)
+        }
+        BenchmarkKind::Ensemble => {
+            if args.members == 0 {
+                return Err(CliError::Data("zespół potrzebuje co najmniej jednego członka (--members)".to_string()));
+            }
+            let dir = PathBuf::from(args.output.as_deref().unwrap_or("."));
+            fs::create_dir_all(&dir).map_err(output_error)?;
+            ensemble::compare_ensembles(args.members, args.seed, &dir).map_err(output_error)
+        }
+        BenchmarkKind::Precision => {
+            let path = args.output.as_deref().unwrap_or("precision.csv");
+            float::compare_precision(args.seed, path).map_err(output_error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_train_predict_evaluate_and_exit_codes() {
+        let temp = |name: &str| {
+            std::env::temp_dir()
+                .join(format!("mlp_cli_{}_{}", std::process::id(), name))
+                .to_string_lossy()
+                .into_owned()
+        };
+        let (model, history, output) = (temp("model.json"), temp("history.csv"), temp("predictions.csv"));
+        let parse = |args: &[&str]| Cli::try_parse_from(std::iter::once("mlp").chain(args.iter().copied())).unwrap();
+
+        let data = ["--function", "sinc", "--dim", "1", "--samples", "50"];
+        let mut train = vec!["train", "--hidden", "4", "--steps", "200", "--batch-size", "8"];
+        train.extend(data);
+        train.extend(["--model", &model, "--history", &history]);
+        run(parse(&train)).unwrap();
+
+        let mut predict = vec!["predict", "--model", &model, "--output", &output];
+        predict.extend(data);
+        run(parse(&predict)).unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap().lines().count(), 50);
+
+        let mut evaluate = vec!["evaluate", "--model", &model];
+        evaluate.extend(data);
+        run(parse(&evaluate)).unwrap();
+
+        // Model o jednym wejściu nie pasuje do danych dwuwymiarowych
+        let err = run(parse(&["evaluate", "--model", &model])).unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::from(3));
+        let err = run(parse(&["evaluate", "--model", &temp("missing.json")])).unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::from(4));
+        let err = run(parse(&["train", "--data", &temp("missing.csv"), "--features", "a", "--target", "y"])).unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::from(3));
+
+        // Wznowienie z punktu kontrolnego innej sieci lub innej precyzji to błąd modelu
+        let checkpoint = temp("checkpoint.json");
+        let mut checkpointed = train.clone();
+        checkpointed.extend(["--checkpoint-every", "100", "--checkpoint", &checkpoint]);
+        run(parse(&checkpointed)).unwrap();
+        checkpointed.push("--resume");
+        run(parse(&checkpointed)).unwrap();
+        let mut wider = checkpointed.clone();
+        wider[2] = "5"; // --hidden 5 zamiast 4
+        let mut single = checkpointed.clone();
+        single.extend(["--precision", "f32"]);
+        for resumed in [wider, single] {
+            assert_eq!(run(parse(&resumed)).unwrap_err().exit_code(), ExitCode::from(4), "{:?}", resumed);
+        }
+        fs::remove_file(&checkpoint).unwrap();
+
+        // Dziedzina funkcji wybrana w czasie działania
+        let loaded = parse(&["evaluate", "--function", "circles", "--min", "-3", "--max", "5"]);
+        let Command::Evaluate(args) = loaded.command else { unreachable!() };
+        let loaded = args.data.load().unwrap();
+        assert!(loaded.features.iter().flatten().all(|v| (-3.0..=5.0).contains(v)));
+        assert!(loaded.features.iter().flatten().any(|&v| v < -1.0));
+
+        // Puste dane i niepoprawne ustawienia uczenia to błędy danych, a nie paniki
+        for args in [
+            &["train", "--samples", "0"][..],
+            &["train", "--hidden", "0"],
+            &["train", "--dropout", "1"],
+            &["train", "--clip-norm", "-1"],
+            &["train", "--clip-norm", "0"],
+            &["train", "--learning-rate", "0"],
+            &["train", "--learning-rate", "-0.1"],
+            &["train", "--learning-rate", "NaN"],
+            &["plot", "--hidden", "0"],
+            &["plot", "--steps", "0"],
+            &["plot", "--learning-rate", "0"],
+            &["train", "--optimizer", "lbfgs", "--resume"],
+            &["train", "--min", "1", "--max", "-1"],
+        ] {
+            assert_eq!(run(parse(args)).unwrap_err().exit_code(), ExitCode::from(3), "{:?}", args);
+        }
+        let err = run(parse(&["evaluate", "--model", &model, "--samples", "0"])).unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::from(3));
+
+        for folds in ["0", "1", "1001"] {
+            let err = run(parse(&["benchmark", "--folds", folds])).unwrap_err();
+            assert_eq!(err.exit_code(), ExitCode::from(3));
+        }
+        let err = run(parse(&["benchmark", "--kind", "ensemble", "--members", "0"])).unwrap_err();
+        assert_eq!(err.exit_code(), ExitCode::from(3));
+
+        // Niepoprawne argumenty zgłasza parser (kod 2)
+        assert!(Cli::try_parse_from(["mlp", "train", "--optimizer", "adam"]).is_err());
+
+        for path in [model, history, output] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Miary jakości regresji.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Metrics {
+    pub(crate) mse: f64,
+    pub(crate) rmse: f64,
+    pub(crate) mae: f64,
+    pub(crate) r2: f64,
+}
+
+impl Metrics {
+    /// Oblicza miary dla predykcji i wartości docelowych.
+    pub(crate) fn compute(predictions: &[f64], targets: &[f64]) -> Metrics {
+        let n = targets.len() as f64;
+        let mse = predictions.iter().zip(targets).map(|(y, t)| (y - t).powi(2)).sum::<f64>() / n;
+        let mae = predictions.iter().zip(targets).map(|(y, t)| (y - t).abs()).sum::<f64>() / n;
+        let mean = targets.iter().sum::<f64>() / n;
+        let total = targets.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / n;
+        Metrics {
+            mse,
+            rmse: mse.sqrt(),
+            mae,
+            r2: if total > 0.0 { 1.0 - mse / total } else { 0.0 },
+        }
+    }
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MSE: {:.6}, RMSE: {:.6}, MAE: {:.6}, R²: {:.4}", self.mse, self.rmse, self.mae, self.r2)
+    }
+}
+
+/// Wynik jednego podziału w walidacji krzyżowej.
+pub(crate) struct FoldResult {
+    pub(crate) repeat: usize,
+    pub(crate) fold: usize,
+    pub(crate) metrics: Metrics,
+}
+
+/// Wyniki walidacji krzyżowej dla wszystkich powtórzeń i podziałów.
+pub(crate) struct CrossValidationReport {
+    pub(crate) folds: Vec<FoldResult>,
+}
+
+impl CrossValidationReport {
+    /// Średnia każdej miary ze wszystkich podziałów.
+    pub(crate) fn mean(&self) -> Metrics {
+        let n = self.folds.len() as f64;
+        let sum = |f: fn(&Metrics) -> f64| self.folds.iter().map(|r| f(&r.metrics)).sum::<f64>() / n;
+        Metrics {
+            mse: sum(|m| m.mse),
+            rmse: sum(|m| m.rmse),
+            mae: sum(|m| m.mae),
+            r2: sum(|m| m.r2),
+        }
+    }
+
+    /// Odchylenie standardowe każdej miary ze wszystkich podziałów.
+    pub(crate) fn std(&self) -> Metrics {
+        let n = self.folds.len();
+        let mean = self.mean();
+        let std = |f: fn(&Metrics) -> f64| {
+            if n < 2 {
+                return 0.0;
+            }
+            let mean = f(&mean);
+            let var = self.folds.iter().map(|r| (f(&r.metrics) - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+            var.sqrt()
+        };
+        Metrics {
+            mse: std(|m| m.mse),
+            rmse: std(|m| m.rmse),
+            mae: std(|m| m.mae),
+            r2: std(|m| m.r2),
+        }
+    }
+}
+
+impl fmt::Display for CrossValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for result in self.folds.iter() {
+            writeln!(f, "Powtórzenie {}, podział {}: {}", result.repeat, result.fold, result.metrics)?;
+        }
+        writeln!(f, "Średnia: {}", self.mean())?;
+        write!(f, "Odchylenie: {}", self.std())
+    }
+}
+
+/// Dzieli indeksy `0..n` po przetasowaniu na `k` rozłącznych podziałów o możliwie równych rozmiarach.
+pub(crate) fn k_fold_indices(n: usize, k: usize, seed: u64) -> Vec<Vec<usize>> {
+    let mut indices: Vec<usize> = (0..n).collect();
+    indices.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+    let mut folds: Vec<Vec<usize>> = vec![Vec::new(); k];
+    for (i, index) in indices.into_iter().enumerate() {
+        folds[i % k].push(index);
+    }
+    folds
+}
+
+/// Powtarzana k-krotna walidacja krzyżowa.
+///
+/// # Argumenty
+///
+/// * `data` - Dane wejściowe.
+/// * `targets` - Wartości docelowe.
+/// * `k` - Liczba podziałów.
+/// * `repeats` - Liczba powtórzeń z innym przetasowaniem danych.
+/// * `seed` - Ziarno bazowe; powtórzenie `r` używa ziarna `seed + r`.
+/// * `fit_predict` - Uczy model na `(dane, wartości)` z podanym ziarnem i zwraca
+///   predykcje dla danych walidacyjnych albo błąd, który przerywa walidację.
+///
+/// # Panika
+///
+/// Gdy `k` jest spoza przedziału `[2, data.len()]`: przy jednym podziale zbiór uczący
+/// byłby pusty, a przy większej liczbie podziałów niż próbek puste byłyby podziały walidacyjne.
+pub(crate) fn cross_validate<F, E>(
+    data: &[Vec<f64>],
+    targets: &[f64],
+    k: usize,
+    repeats: usize,
+    seed: u64,
+    fit_predict: F,
+) -> Result<CrossValidationReport, E>
+where
+    F: Fn(&[Vec<f64>], &[f64], &[Vec<f64>], u64) -> Result<Vec<f64>, E>,
+{
+    assert!(
+        (2..=data.len()).contains(&k),
+        "liczba podziałów {} spoza przedziału [2, {}]",
+        k,
+        data.len()
+    );
+    let mut folds = Vec::with_capacity(k * repeats);
+    for repeat in 0..repeats {
+        let repeat_seed = seed + repeat as u64;
+        let split = k_fold_indices(data.len(), k, repeat_seed);
+        for (fold, validation_indices) in split.iter().enumerate() {
+            let train_indices: Vec<usize> = split
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != fold)
+                .flat_map(|(_, indices)| indices.iter().copied())
+                .collect();
+
+            let train_data: Vec<Vec<f64>> = train_indices.iter().map(|&i| data[i].clone()).collect();
+            let train_targets: Vec<f64> = train_indices.iter().map(|&i| targets[i]).collect();
+            let validation_data: Vec<Vec<f64>> = validation_indices.iter().map(|&i| data[i].clone()).collect();
+            let validation_targets: Vec<f64> = validation_indices.iter().map(|&i| targets[i]).collect();
+
+            let predictions = fit_predict(&train_data, &train_targets, &validation_data, repeat_seed)?;
+            folds.push(FoldResult {
+                repeat,
+                fold,
+                metrics: Metrics::compute(&predictions, &validation_targets),
+            });
+        }
+    }
+    Ok(CrossValidationReport { folds })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_k_fold_partitions_and_metrics() {
+        let folds = k_fold_indices(10, 3, 1);
+        let sizes: Vec<usize> = folds.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![4, 3, 3]);
+        let mut all: Vec<usize> = folds.concat();
+        all.sort_unstable();
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+
+        let metrics = Metrics::compute(&[1.0, 2.0, 3.0], &[1.0, 2.0, 5.0]);
+        assert!((metrics.mse - 4.0 / 3.0).abs() < 1e-12);
+        assert!((metrics.mae - 2.0 / 3.0).abs() < 1e-12);
+        // Średnia wartości docelowych to 8/3, suma kwadratów odchyleń 26/3, a suma kwadratów błędów 4
+        assert!((metrics.r2 - 7.0 / 13.0).abs() < 1e-12);
+
+        // Model zwracający średnią ze zbioru uczącego
+        let data: Vec<Vec<f64>> = (0..20).map(|i| vec![i as f64]).collect();
+        let targets: Vec<f64> = (0..20).map(|i| i as f64).collect();
+        let report = cross_validate(&data, &targets, 5, 2, 7, |_, train_targets, validation, _| {
+            let mean = train_targets.iter().sum::<f64>() / train_targets.len() as f64;
+            Ok::<_, ()>(vec![mean; validation.len()])
+        })
+        .unwrap();
+        assert_eq!(report.folds.len(), 10);
+        assert!(report.mean().r2 < 0.0);
+
+        for k in [0, 1, 21] {
+            let result = std::panic::catch_unwind(|| {
+                cross_validate(&data, &targets, k, 1, 7, |_, _, validation, _| Ok::<_, ()>(vec![0.0; validation.len()]))
+            });
+            assert!(result.is_err(), "k = {}", k);
+        }
+    }
+}
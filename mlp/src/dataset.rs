@@ -0,0 +1,153 @@
+use std::fmt;
+
+use csv::{ReaderBuilder, Trim};
+
+/// Błąd wczytywania zbioru danych z pliku CSV.
+#[derive(Debug)]
+pub(crate) enum DatasetError {
+    Csv(csv::Error),
+    MissingColumn(String),
+    InvalidValue { row: usize, column: String, value: String },
+    Empty,
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatasetError::Csv(e) => write!(f, "błąd odczytu CSV: {}", e),
+            DatasetError::MissingColumn(name) => write!(f, "brak kolumny '{}' w nagłówku", name),
+            DatasetError::InvalidValue { row, column, value } => write!(
+                f,
+                "wiersz {}, kolumna '{}': wartość '{}' nie jest skończoną liczbą",
+                row, column, value
+            ),
+            DatasetError::Empty => write!(f, "plik nie zawiera żadnych wierszy danych"),
+        }
+    }
+}
+
+impl From<csv::Error> for DatasetError {
+    fn from(e: csv::Error) -> Self {
+        DatasetError::Csv(e)
+    }
+}
+
+/// Zbiór danych wczytany z pliku CSV: wybrane kolumny cech i kolumny docelowe.
+pub(crate) struct Dataset {
+    pub(crate) feature_names: Vec<String>,
+    pub(crate) target_names: Vec<String>,
+    pub(crate) features: Vec<Vec<f64>>,
+    pub(crate) targets: Vec<Vec<f64>>,
+}
+
+impl Dataset {
+    /// Wczytuje plik CSV z nagłówkiem, wybierając kolumny po nazwach.
+    ///
+    /// # Argumenty
+    ///
+    /// * `path` - Ścieżka pliku CSV.
+    /// * `feature_columns` - Nazwy kolumn używanych jako wejścia sieci.
+    /// * `target_columns` - Nazwy kolumn z wartościami docelowymi.
+    ///
+    /// # Zwraca
+    ///
+    /// Zbiór danych albo błąd wskazujący brakującą kolumnę lub wiersz z niepoprawną wartością.
+    pub(crate) fn from_csv(
+        path: &str,
+        feature_columns: &[&str],
+        target_columns: &[&str],
+    ) -> Result<Dataset, DatasetError> {
+        let mut reader = ReaderBuilder::new()
+            .has_headers(true)
+            .trim(Trim::All)
+            .from_path(path)?;
+
+        // Wyszukanie indeksów wybranych kolumn w nagłówku
+        let headers = reader.headers()?.clone();
+        let find = |name: &&str| {
+            headers
+                .iter()
+                .position(|h| h == *name)
+                .ok_or_else(|| DatasetError::MissingColumn(name.to_string()))
+        };
+        let feature_indices: Vec<usize> = feature_columns.iter().map(find).collect::<Result<_, _>>()?;
+        let target_indices: Vec<usize> = target_columns.iter().map(find).collect::<Result<_, _>>()?;
+
+        let mut features: Vec<Vec<f64>> = Vec::new();
+        let mut targets: Vec<Vec<f64>> = Vec::new();
+        for (i, record) in reader.records().enumerate() {
+            let record = record?;
+            // Numer wiersza w pliku, licząc nagłówek jako wiersz 1
+            let row = i + 2;
+            let parse = |&index: &usize| {
+                let value = record.get(index).unwrap_or("");
+                // `str::parse` przyjmuje też "NaN" i "inf", które zepsułyby uczenie
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+                    .ok_or_else(|| DatasetError::InvalidValue {
+                        row,
+                        column: headers[index].to_string(),
+                        value: value.to_string(),
+                    })
+            };
+            features.push(feature_indices.iter().map(parse).collect::<Result<_, _>>()?);
+            targets.push(target_indices.iter().map(parse).collect::<Result<_, _>>()?);
+        }
+
+        if features.is_empty() {
+            return Err(DatasetError::Empty);
+        }
+
+        Ok(Dataset {
+            feature_names: feature_columns.iter().map(|s| s.to_string()).collect(),
+            target_names: target_columns.iter().map(|s| s.to_string()).collect(),
+            features,
+            targets,
+        })
+    }
+
+    /// Wartości pierwszej kolumny docelowej, w postaci oczekiwanej przez `MLP::fit`.
+    pub(crate) fn target_values(&self) -> Vec<f64> {
+        self.targets.iter().map(|t| t[0]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_from_csv_selects_and_validates_columns() {
+        let path = std::env::temp_dir().join(format!("mlp_dataset_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        fs::write(path, "a, b, c, y\n1, 2, 3, 0.5\n4, 5, 6, 1.5\n").unwrap();
+        let dataset = Dataset::from_csv(path, &["c", "a"], &["y"]).unwrap();
+        assert_eq!(dataset.features, vec![vec![3.0, 1.0], vec![6.0, 4.0]]);
+        assert_eq!(dataset.target_values(), vec![0.5, 1.5]);
+
+        assert!(matches!(
+            Dataset::from_csv(path, &["a", "z"], &["y"]),
+            Err(DatasetError::MissingColumn(name)) if name == "z"
+        ));
+
+        fs::write(path, "a,y\n1,0.5\nx,1.5\n").unwrap();
+        assert!(matches!(
+            Dataset::from_csv(path, &["a"], &["y"]),
+            Err(DatasetError::InvalidValue { row: 3, .. })
+        ));
+
+        for value in ["NaN", "inf", "-inf"] {
+            fs::write(path, format!("a,y\n1,0.5\n2,{}\n", value)).unwrap();
+            assert!(matches!(
+                Dataset::from_csv(path, &["a"], &["y"]),
+                Err(DatasetError::InvalidValue { row: 3, column, .. }) if column == "y"
+            ));
+        }
+
+        fs::remove_file(path).unwrap();
+    }
+}
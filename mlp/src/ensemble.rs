@@ -0,0 +1,254 @@
+use std::error::Error;
+use std::path::Path;
+
+use csv::Writer;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+use crate::functions::TargetFunction;
+use crate::loss::Loss;
+use crate::model_file::ModelError;
+use crate::plots;
+use crate::{generate_data, generate_training_data, Regressor, MLP, RNG_SEED, TEST_RNG_SEED};
+
+/// Sposób zróżnicowania członków zespołu.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Resampling {
+    Seeds,   // Te same dane, różne ziarna inicjalizacji i losowania próbek
+    Bagging, // Różne ziarna i próbki bootstrapowe (losowanie ze zwracaniem) danych uczących
+}
+
+impl Resampling {
+    /// Krótka nazwa używana w nazwach plików i wynikach.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Resampling::Seeds => "seeds",
+            Resampling::Bagging => "bagging",
+        }
+    }
+}
+
+/// Zespół sieci MLP uśredniający predykcje członków.
+pub(crate) struct Ensemble {
+    pub(crate) members: Vec<MLP>,
+}
+
+impl Ensemble {
+    /// Równolegle uczy `size` sieci; członek `i` dostaje ziarno `seed + i`.
+    ///
+    /// # Argumenty
+    ///
+    /// * `build` - Tworzy nienauczoną sieć dla podanego ziarna.
+    /// * `size` - Liczba członków zespołu.
+    /// * `resampling` - Sposób zróżnicowania członków.
+    /// * `data` - Dane wejściowe.
+    /// * `targets` - Wartości docelowe.
+    /// * `seed` - Ziarno bazowe.
+    ///
+    /// # Panika
+    ///
+    /// Gdy `size` wynosi 0: średnia i odchylenie pustego zespołu nie są określone.
+    pub(crate) fn fit<F>(
+        build: F,
+        size: usize,
+        resampling: Resampling,
+        data: &[Vec<f64>],
+        targets: &[f64],
+        seed: u64,
+    ) -> Ensemble
+    where
+        F: Fn(u64) -> MLP + Sync,
+    {
+        assert!(size >= 1, "zespół potrzebuje co najmniej jednego członka");
+        let members = (0..size as u64)
+            .into_par_iter()
+            .map(|i| {
+                let member_seed = seed + i;
+                let mut mlp = build(member_seed);
+                match resampling {
+                    Resampling::Seeds => {
+                        mlp.fit(data, targets);
+                    }
+                    Resampling::Bagging => {
+                        let mut rng = ChaCha8Rng::seed_from_u64(member_seed);
+                        let sample: Vec<usize> = (0..data.len()).map(|_| rng.gen_range(0..data.len())).collect();
+                        let sample_data: Vec<Vec<f64>> = sample.iter().map(|&j| data[j].clone()).collect();
+                        let sample_targets: Vec<f64> = sample.iter().map(|&j| targets[j]).collect();
+                        mlp.fit(&sample_data, &sample_targets);
+                    }
+                }
+                mlp
+            })
+            .collect();
+        Ensemble { members }
+    }
+
+    /// Średnia i odchylenie standardowe predykcji członków zespołu dla każdego punktu danych.
+    ///
+    /// # Argumenty
+    ///
+    /// * `data` - Wycinek wektorów reprezentujących punkty danych wejściowych.
+    ///
+    /// # Zwraca
+    ///
+    /// Wektor średnich i wektor odchyleń standardowych (0 dla jednego członka).
+    pub(crate) fn predict_with_std(&self, data: &[Vec<f64>]) -> (Vec<f64>, Vec<f64>) {
+        let predictions: Vec<Vec<f64>> = self.members.par_iter().map(|mlp| mlp.predict(data)).collect();
+        let n = predictions.len() as f64;
+        (0..data.len())
+            .map(|i| {
+                let mean = predictions.iter().map(|p| p[i]).sum::<f64>() / n;
+                if predictions.len() < 2 {
+                    return (mean, 0.0);
+                }
+                let var = predictions.iter().map(|p| (p[i] - mean).powi(2)).sum::<f64>() / (n - 1.0);
+                (mean, var.sqrt())
+            })
+            .unzip()
+    }
+
+    /// Zapisuje średnią i odchylenie standardowe predykcji na siatce `plots::grid_points`
+    /// do pliku CSV z kolumnami `x1`, `x2`, `mean`, `std`.
+    ///
+    /// # Argumenty
+    ///
+    /// * `min`, `max` - Dziedzina każdej ze współrzędnych.
+    /// * `path` - Ścieżka pliku CSV.
+    pub(crate) fn save_uncertainty_csv(&self, min: f64, max: f64, path: &str) -> Result<(), csv::Error> {
+        let grid = plots::grid_points(min, max);
+        let (mean, std) = self.predict_with_std(&grid);
+        let mut wtr = Writer::from_path(path)?;
+        wtr.write_record(["x1", "x2", "mean", "std"])?;
+        for ((x, m), s) in grid.iter().zip(mean.iter()).zip(std.iter()) {
+            wtr.write_record(&[x[0].to_string(), x[1].to_string(), m.to_string(), s.to_string()])?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl Regressor for Ensemble {
+    fn fit(&mut self, data: &[Vec<f64>], targets: &[f64]) -> Result<(), ModelError> {
+        self.members.par_iter_mut().for_each(|mlp| {
+            mlp.fit(data, targets);
+        });
+        Ok(())
+    }
+
+    fn predict(&self, data: &[Vec<f64>]) -> Vec<f64> {
+        self.predict_with_std(data).0
+    }
+}
+
+/// Współczynnik korelacji Pearsona.
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let (mean_a, mean_b) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+    let cov: f64 = a.iter().zip(b.iter()).map(|(x, y)| (x - mean_a) * (y - mean_b)).sum();
+    let var_a: f64 = a.iter().map(|x| (x - mean_a).powi(2)).sum();
+    let var_b: f64 = b.iter().map(|y| (y - mean_b).powi(2)).sum();
+    cov / (var_a * var_b).sqrt()
+}
+
+/// Uczy zespoły obu rodzajów na cos(x*y)*cos(2x) z pominiętym narożnikiem dziedziny
+/// (`x1 > 2` i `x2 > 2`) i zapisuje mapy niepewności `uncertainty_<rodzaj>.csv` i `.svg`.
+///
+/// Wypisuje MSE średniej predykcji, średnie odchylenie standardowe w danych i poza nimi
+/// oraz korelację odchylenia z błędem bezwzględnym na siatce.
+///
+/// # Argumenty
+///
+/// * `size` - Liczba członków każdego zespołu.
+/// * `seed` - Ziarno bazowe sieci.
+/// * `out` - Katalog plików wynikowych.
+pub(crate) fn compare_ensembles(size: usize, seed: u64, out: &Path) -> Result<(), Box<dyn Error>> {
+    let (min, max) = (0.0, std::f64::consts::PI);
+    let held_out = |x: &[f64]| x[0] > 2.0 && x[1] > 2.0;
+    let data: Vec<Vec<f64>> = generate_data(1000, min, max, RNG_SEED)
+        .into_iter()
+        .filter(|x| !held_out(x))
+        .collect();
+    let targets = generate_training_data(&data);
+    let test_data = generate_data(1000, min, max, TEST_RNG_SEED);
+    let test_targets = generate_training_data(&test_data);
+    let grid = plots::grid_points(min, max);
+    let grid_targets: Vec<f64> = grid.iter().map(|x| TargetFunction::CosProduct.evaluate(x, (min, max))).collect();
+
+    println!(
+        "{:<8} {:>12} {:>14} {:>14} {:>12}",
+        "Zespół", "MSE testowe", "std w danych", "std poza nimi", "kor(std, |e|)"
+    );
+    for resampling in [Resampling::Seeds, Resampling::Bagging] {
+        let build = |seed| {
+            let mut mlp = MLP::new(2, 20, 1, 100_000, 0.1, seed);
+            mlp.config.eval_every = 100_000;
+            mlp
+        };
+        let ensemble = Ensemble::fit(build, size, resampling, &data, &targets, seed);
+
+        let (mean, std) = ensemble.predict_with_std(&grid);
+        let errors: Vec<f64> = mean.iter().zip(grid_targets.iter()).map(|(m, t)| (m - t).abs()).collect();
+        let mean_std = |inside: bool| {
+            let values: Vec<f64> = grid
+                .iter()
+                .zip(std.iter())
+                .filter(|(x, _)| held_out(x) != inside)
+                .map(|(_, s)| *s)
+                .collect();
+            values.iter().sum::<f64>() / values.len() as f64
+        };
+        println!(
+            "{:<8} {:>12.6} {:>14.6} {:>14.6} {:>12.4}",
+            resampling.name(),
+            Loss::MSE.mean(&ensemble.predict(&test_data), &test_targets),
+            mean_std(true),
+            mean_std(false),
+            correlation(&std, &errors)
+        );
+
+        let path = |extension: &str| {
+            out.join(format!("uncertainty_{}.{}", resampling.name(), extension))
+                .to_string_lossy()
+                .into_owned()
+        };
+        ensemble.save_uncertainty_csv(min, max, &path("csv"))?;
+        plots::plot_uncertainty_map(&ensemble, &data, min, max, &path("svg"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_ensemble_mean_and_std() {
+        let data = generate_data(100, 0.0, PI, RNG_SEED);
+        let targets = generate_training_data(&data);
+        let build = |seed| MLP::new(2, 5, 1, 500, 0.1, seed);
+
+        let ensemble = Ensemble::fit(build, 4, Resampling::Seeds, &data, &targets, 10);
+        let (mean, std) = ensemble.predict_with_std(&data[..10]);
+        for i in 0..10 {
+            let member: Vec<f64> = ensemble.members.iter().map(|m| m.predict(&data[i..=i])[0]).collect();
+            assert!((mean[i] - member.iter().sum::<f64>() / 4.0).abs() < 1e-12);
+            assert!(std[i] > 0.0);
+        }
+
+        // Ten sam zestaw ziaren daje ten sam zespół niezależnie od kolejności wątków
+        let again = Ensemble::fit(build, 4, Resampling::Seeds, &data, &targets, 10);
+        assert_eq!(again.predict_with_std(&data[..10]), (mean.clone(), std));
+
+        // Próbki bootstrapowe zmieniają członków
+        let bagged = Ensemble::fit(build, 4, Resampling::Bagging, &data, &targets, 10);
+        assert_ne!(bagged.predict(&data[..10]), mean);
+
+        let single = Ensemble::fit(build, 1, Resampling::Seeds, &data, &targets, 10);
+        assert!(single.predict_with_std(&data).1.iter().all(|&s| s == 0.0));
+
+        let empty = std::panic::catch_unwind(|| Ensemble::fit(build, 0, Resampling::Seeds, &data, &targets, 10));
+        assert!(empty.is_err());
+    }
+}
@@ -0,0 +1,560 @@
+use std::time::Instant;
+
+use csv::Writer;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::StandardNormal;
+
+use crate::float::{cast, Float};
+use crate::functions::{Problem, TargetFunction};
+use crate::loss::Loss;
+use crate::matrix::{dot, Matrix};
+use crate::scaling::Scaler;
+use crate::{RNG_SEED, TEST_RNG_SEED, MLP};
+
+/// Odchylenie standardowe początkowego rozrzutu populacji wokół wag z `MLP::new`.
+const INITIAL_SIGMA: f64 = 1.0;
+/// Liczność populacji algorytmu genetycznego, ewolucji różnicowej i roju cząstek.
+const POPULATION: usize = 40;
+
+/// Bezgradientowa metoda optymalizacji wag.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum GradientFree {
+    Genetic,               // Algorytm genetyczny z kodowaniem rzeczywistym
+    DifferentialEvolution, // Ewolucja różnicowa DE/rand/1/bin
+    ParticleSwarm,         // Optymalizacja rojem cząstek ze współczynnikiem ścisku
+    CmaEs,                 // Strategia ewolucyjna z adaptacją macierzy kowariancji
+}
+
+/// Wszystkie metody bezgradientowe, w kolejności porównania.
+pub(crate) const GRADIENT_FREE: [GradientFree; 4] = [
+    GradientFree::Genetic,
+    GradientFree::DifferentialEvolution,
+    GradientFree::ParticleSwarm,
+    GradientFree::CmaEs,
+];
+
+impl GradientFree {
+    /// Krótka nazwa metody używana w wynikach.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            GradientFree::Genetic => "ga",
+            GradientFree::DifferentialEvolution => "de",
+            GradientFree::ParticleSwarm => "pso",
+            GradientFree::CmaEs => "cma-es",
+        }
+    }
+}
+
+/// Przebieg optymalizacji: najmniejsza strata po kolejnych liczbach ewaluacji.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SearchTrace {
+    pub(crate) evaluations: Vec<usize>,
+    pub(crate) best_loss: Vec<f64>,
+}
+
+/// Funkcja celu z limitem ewaluacji, zapamiętująca najlepszy dotąd punkt.
+struct Objective<'a> {
+    f: &'a mut dyn FnMut(&[f64]) -> f64,
+    budget: usize,
+    evaluations: usize,
+    record_every: usize,
+    best: (f64, Vec<f64>),
+    trace: SearchTrace,
+}
+
+impl<'a> Objective<'a> {
+    fn new(f: &'a mut dyn FnMut(&[f64]) -> f64, budget: usize) -> Self {
+        Objective {
+            f,
+            budget,
+            evaluations: 0,
+            record_every: (budget / 200).max(1),
+            best: (f64::INFINITY, Vec::new()),
+            trace: SearchTrace::default(),
+        }
+    }
+
+    /// Wartość funkcji celu w punkcie `x` albo `None`, gdy limit ewaluacji został wyczerpany.
+    fn evaluate(&mut self, x: &[f64]) -> Option<f64> {
+        if self.evaluations >= self.budget {
+            return None;
+        }
+        let value = (self.f)(x);
+        self.evaluations += 1;
+        // NaN traktowany jest jak najgorsza możliwa wartość
+        let value = if value.is_nan() { f64::INFINITY } else { value };
+        if value < self.best.0 {
+            self.best = (value, x.to_vec());
+        }
+        if self.evaluations.is_multiple_of(self.record_every) || self.evaluations == self.budget {
+            self.trace.evaluations.push(self.evaluations);
+            self.trace.best_loss.push(self.best.0);
+        }
+        Some(value)
+    }
+}
+
+/// Punkt `x0 + sigma * z`, gdzie `z` ma standardowy rozkład normalny.
+fn perturb(x0: &[f64], sigma: f64, rng: &mut ChaCha8Rng) -> Vec<f64> {
+    x0.iter()
+        .map(|x| x + sigma * rng.sample::<f64, _>(StandardNormal))
+        .collect()
+}
+
+/// Indeks najmniejszej wartości.
+fn argmin(values: &[f64]) -> usize {
+    (0..values.len())
+        .min_by(|&a, &b| values[a].total_cmp(&values[b]))
+        .unwrap_or_default()
+}
+
+/// Algorytm genetyczny: selekcja turniejowa, krzyżowanie BLX-α, mutacja gaussowska
+/// i elitaryzm (najlepszy osobnik przechodzi do kolejnego pokolenia bez zmian).
+fn genetic(objective: &mut Objective, x0: &[f64], rng: &mut ChaCha8Rng) {
+    const TOURNAMENT_SIZE: usize = 3;
+    const P_C: f64 = 0.9; // Prawdopodobieństwo krzyżowania
+    const ALPHA: f64 = 0.5; // Rozszerzenie przedziału krzyżowania BLX-α
+    const MUTATION_SIGMA: f64 = 0.1;
+    let p_m = 1.0 / x0.len() as f64; // Prawdopodobieństwo mutacji pojedynczego genu
+
+    let mut population: Vec<Vec<f64>> = (0..POPULATION).map(|_| perturb(x0, INITIAL_SIGMA, rng)).collect();
+    let mut fitness = Vec::with_capacity(POPULATION);
+    for individual in population.iter() {
+        let Some(f) = objective.evaluate(individual) else { return };
+        fitness.push(f);
+    }
+
+    loop {
+        let tournament = |rng: &mut ChaCha8Rng| -> usize {
+            (0..TOURNAMENT_SIZE)
+                .map(|_| rng.gen_range(0..POPULATION))
+                .min_by(|&a, &b| fitness[a].total_cmp(&fitness[b]))
+                .unwrap_or_default()
+        };
+
+        let elite = argmin(&fitness);
+        let mut offspring = vec![population[elite].clone()];
+        while offspring.len() < POPULATION {
+            let (a, b) = (&population[tournament(rng)], &population[tournament(rng)]);
+            let mut child: Vec<f64> = if rng.gen_bool(P_C) {
+                a.iter()
+                    .zip(b.iter())
+                    .map(|(&a, &b)| {
+                        let (low, high) = (a.min(b), a.max(b));
+                        let spread = ALPHA * (high - low);
+                        rng.gen_range(low - spread..=high + spread)
+                    })
+                    .collect()
+            } else {
+                a.clone()
+            };
+            for gene in child.iter_mut() {
+                if rng.gen_bool(p_m) {
+                    *gene += MUTATION_SIGMA * rng.sample::<f64, _>(StandardNormal);
+                }
+            }
+            offspring.push(child);
+        }
+
+        // Elita zachowuje swoją ocenę; pozostali potomkowie są oceniani
+        let mut new_fitness = vec![fitness[elite]];
+        for child in offspring[1..].iter() {
+            let Some(f) = objective.evaluate(child) else { return };
+            new_fitness.push(f);
+        }
+        population = offspring;
+        fitness = new_fitness;
+    }
+}
+
+/// Ewolucja różnicowa DE/rand/1/bin z zachłanną selekcją.
+fn differential_evolution(objective: &mut Objective, x0: &[f64], rng: &mut ChaCha8Rng) {
+    const F: f64 = 0.5; // Waga różnicy wektorów
+    const CR: f64 = 0.9; // Prawdopodobieństwo krzyżowania
+    let dim = x0.len();
+
+    let mut population: Vec<Vec<f64>> = (0..POPULATION).map(|_| perturb(x0, INITIAL_SIGMA, rng)).collect();
+    let mut fitness = Vec::with_capacity(POPULATION);
+    for individual in population.iter() {
+        let Some(f) = objective.evaluate(individual) else { return };
+        fitness.push(f);
+    }
+
+    let candidates: Vec<usize> = (0..POPULATION).collect();
+    loop {
+        for i in 0..POPULATION {
+            // Trzy różne osobniki, różne od `i`
+            let mut chosen = candidates.choose_multiple(rng, 4).copied().filter(|&j| j != i);
+            let (Some(a), Some(b), Some(c)) = (chosen.next(), chosen.next(), chosen.next()) else {
+                continue;
+            };
+            let forced = rng.gen_range(0..dim);
+            let trial: Vec<f64> = (0..dim)
+                .map(|d| {
+                    if d == forced || rng.gen_bool(CR) {
+                        population[a][d] + F * (population[b][d] - population[c][d])
+                    } else {
+                        population[i][d]
+                    }
+                })
+                .collect();
+            let Some(f) = objective.evaluate(&trial) else { return };
+            if f <= fitness[i] {
+                population[i] = trial;
+                fitness[i] = f;
+            }
+        }
+    }
+}
+
+/// Optymalizacja rojem cząstek z globalną topologią i współczynnikami ścisku Clerca.
+fn particle_swarm(objective: &mut Objective, x0: &[f64], rng: &mut ChaCha8Rng) {
+    const INERTIA: f64 = 0.7298;
+    const C1: f64 = 1.49618; // Przyciąganie do najlepszej pozycji cząstki
+    const C2: f64 = 1.49618; // Przyciąganie do najlepszej pozycji roju
+
+    let mut positions: Vec<Vec<f64>> = (0..POPULATION).map(|_| perturb(x0, INITIAL_SIGMA, rng)).collect();
+    let mut velocities: Vec<Vec<f64>> = (0..POPULATION)
+        .map(|_| perturb(&vec![0.0; x0.len()], 0.1 * INITIAL_SIGMA, rng))
+        .collect();
+    let mut personal_best = positions.clone();
+    let mut personal_fitness = Vec::with_capacity(POPULATION);
+    for position in positions.iter() {
+        let Some(f) = objective.evaluate(position) else { return };
+        personal_fitness.push(f);
+    }
+
+    loop {
+        let global = personal_best[argmin(&personal_fitness)].clone();
+        for i in 0..POPULATION {
+            for d in 0..x0.len() {
+                let (r1, r2): (f64, f64) = (rng.gen(), rng.gen());
+                velocities[i][d] = INERTIA * velocities[i][d]
+                    + C1 * r1 * (personal_best[i][d] - positions[i][d])
+                    + C2 * r2 * (global[d] - positions[i][d]);
+                positions[i][d] += velocities[i][d];
+            }
+            let Some(f) = objective.evaluate(&positions[i]) else { return };
+            if f < personal_fitness[i] {
+                personal_best[i] = positions[i].clone();
+                personal_fitness[i] = f;
+            }
+        }
+    }
+}
+
+/// Wartości i wektory własne macierzy symetrycznej (metoda obrotów Jacobiego).
+///
+/// # Zwraca
+///
+/// Wartości własne i macierz, której kolumny są odpowiadającymi im wektorami własnymi.
+fn symmetric_eigen(matrix: &Matrix) -> (Vec<f64>, Matrix) {
+    let n = matrix.rows();
+    let mut a = matrix.clone();
+    let mut v = Matrix::zeros(n, n);
+    for i in 0..n {
+        v.row_mut(i)[i] = 1.0;
+    }
+
+    for _ in 0..50 {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a.row(i)[j].powi(2))
+            .sum();
+        if off < 1e-22 {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = a.row(p)[q];
+                if apq.abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a.row(q)[q] - a.row(p)[p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let (c, s) = (1.0 / (t * t + 1.0).sqrt(), t / (t * t + 1.0).sqrt());
+                for k in 0..n {
+                    let (akp, akq) = (a.row(k)[p], a.row(k)[q]);
+                    a.row_mut(k)[p] = c * akp - s * akq;
+                    a.row_mut(k)[q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a.row(p)[k], a.row(q)[k]);
+                    a.row_mut(p)[k] = c * apk - s * aqk;
+                    a.row_mut(q)[k] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (v.row(k)[p], v.row(k)[q]);
+                    v.row_mut(k)[p] = c * vkp - s * vkq;
+                    v.row_mut(k)[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    ((0..n).map(|i| a.row(i)[i]).collect(), v)
+}
+
+/// CMA-ES (μ/μ_w, λ) z pełną macierzą kowariancji i adaptacją długości kroku
+/// według ścieżki ewolucyjnej (parametry domyślne według Hansena).
+fn cma_es(objective: &mut Objective, x0: &[f64], rng: &mut ChaCha8Rng) {
+    let n = x0.len();
+    let nf = n as f64;
+    let lambda = 4 + (3.0 * nf.ln()) as usize;
+    let mu = lambda / 2;
+    let raw: Vec<f64> = (1..=mu).map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln()).collect();
+    let weights: Vec<f64> = raw.iter().map(|w| w / raw.iter().sum::<f64>()).collect();
+    let mueff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+    let cc = (4.0 + mueff / nf) / (nf + 4.0 + 2.0 * mueff / nf);
+    let cs = (mueff + 2.0) / (nf + mueff + 5.0);
+    let c1 = 2.0 / ((nf + 1.3).powi(2) + mueff);
+    let cmu = (1.0 - c1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((nf + 2.0).powi(2) + mueff));
+    let damps = 1.0 + 2.0 * (((mueff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+    let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+
+    let mut mean = x0.to_vec();
+    let mut sigma = INITIAL_SIGMA;
+    let mut pc = vec![0.0; n];
+    let mut ps = vec![0.0; n];
+    let mut c = Matrix::zeros(n, n);
+    for i in 0..n {
+        c.row_mut(i)[i] = 1.0;
+    }
+
+    for generation in 1.. {
+        // C = B D² Bᵀ
+        let (eigenvalues, b) = symmetric_eigen(&c);
+        let d: Vec<f64> = eigenvalues.iter().map(|e| e.max(1e-20).sqrt()).collect();
+
+        // Próbkowanie y = B D z oraz x = m + sigma y
+        let mut samples: Vec<(f64, Vec<f64>)> = Vec::with_capacity(lambda);
+        for _ in 0..lambda {
+            let z: Vec<f64> = (0..n).map(|i| d[i] * rng.sample::<f64, _>(StandardNormal)).collect();
+            let y: Vec<f64> = (0..n).map(|i| dot(b.row(i), &z)).collect();
+            let x: Vec<f64> = mean.iter().zip(y.iter()).map(|(m, y)| m + sigma * y).collect();
+            let Some(f) = objective.evaluate(&x) else { return };
+            samples.push((f, y));
+        }
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // Nowa średnia
+        let mut y_w = vec![0.0; n];
+        for ((_, y), w) in samples.iter().zip(weights.iter()) {
+            for (acc, y) in y_w.iter_mut().zip(y.iter()) {
+                *acc += w * y;
+            }
+        }
+        for (m, y) in mean.iter_mut().zip(y_w.iter()) {
+            *m += sigma * y;
+        }
+
+        // Ścieżka długości kroku: C^(-1/2) y_w = B D^(-1) Bᵀ y_w
+        let bt_y: Vec<f64> = (0..n).map(|j| (0..n).map(|i| b.row(i)[j] * y_w[i]).sum::<f64>() / d[j]).collect();
+        let c_inv_sqrt_y: Vec<f64> = (0..n).map(|i| dot(b.row(i), &bt_y)).collect();
+        let ps_factor = (cs * (2.0 - cs) * mueff).sqrt();
+        for (p, v) in ps.iter_mut().zip(c_inv_sqrt_y.iter()) {
+            *p = (1.0 - cs) * *p + ps_factor * v;
+        }
+        let ps_norm = dot(&ps, &ps).sqrt();
+        let hsig = ps_norm / (1.0 - (1.0 - cs).powi(2 * generation)).sqrt() / chi_n < 1.4 + 2.0 / (nf + 1.0);
+
+        // Ścieżka kowariancji
+        let pc_factor = if hsig { (cc * (2.0 - cc) * mueff).sqrt() } else { 0.0 };
+        for (p, y) in pc.iter_mut().zip(y_w.iter()) {
+            *p = (1.0 - cc) * *p + pc_factor * y;
+        }
+
+        // Aktualizacja macierzy kowariancji: rang 1 i rang μ
+        let correction = if hsig { 0.0 } else { c1 * cc * (2.0 - cc) };
+        for i in 0..n {
+            for j in 0..n {
+                let rank_mu: f64 = samples.iter().zip(weights.iter()).map(|((_, y), w)| w * y[i] * y[j]).sum();
+                let cij = c.row(i)[j];
+                c.row_mut(i)[j] = (1.0 - c1 - cmu) * cij + c1 * pc[i] * pc[j] + correction * cij + cmu * rank_mu;
+            }
+        }
+
+        sigma *= ((cs / damps) * (ps_norm / chi_n - 1.0)).exp();
+    }
+}
+
+/// Minimalizuje `f` wybraną metodą, zaczynając od `x0`.
+///
+/// # Argumenty
+///
+/// * `method` - Metoda optymalizacji.
+/// * `f` - Funkcja celu.
+/// * `x0` - Punkt początkowy (środek początkowej populacji).
+/// * `evaluations` - Dokładna liczba ewaluacji funkcji celu.
+/// * `seed` - Ziarno generatora liczb losowych.
+///
+/// # Zwraca
+///
+/// Najlepszy znaleziony punkt, jego wartość i przebieg optymalizacji.
+pub(crate) fn minimize(
+    method: GradientFree,
+    f: &mut dyn FnMut(&[f64]) -> f64,
+    x0: &[f64],
+    evaluations: usize,
+    seed: u64,
+) -> (Vec<f64>, f64, SearchTrace) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut objective = Objective::new(f, evaluations);
+    match method {
+        GradientFree::Genetic => genetic(&mut objective, x0, &mut rng),
+        GradientFree::DifferentialEvolution => differential_evolution(&mut objective, x0, &mut rng),
+        GradientFree::ParticleSwarm => particle_swarm(&mut objective, x0, &mut rng),
+        GradientFree::CmaEs => cma_es(&mut objective, x0, &mut rng),
+    }
+    let (best_loss, best) = objective.best;
+    if best.is_empty() {
+        return (x0.to_vec(), f64::NAN, objective.trace);
+    }
+    (best, best_loss, objective.trace)
+}
+
+impl<F: Float> MLP<F> {
+    /// Dopasowuje wagi sieci metodą bezgradientową, minimalizując stratę na całym zbiorze
+    /// uczącym. Jedna ewaluacja to jedno obliczenie straty na wszystkich próbkach.
+    ///
+    /// Populacje są przechowywane w `f64` niezależnie od `F`; kandydaci są zamieniani na `F`
+    /// przed obliczeniem straty, która dominuje koszt ewaluacji.
+    ///
+    /// Skalery są dopasowywane tak jak w `MLP::fit_outputs`; walidacja i harmonogram
+    /// współczynnika uczenia nie są używane.
+    ///
+    /// # Argumenty
+    ///
+    /// * `data` - Dane wejściowe.
+    /// * `targets` - Wektory docelowe.
+    /// * `method` - Metoda optymalizacji.
+    /// * `evaluations` - Liczba ewaluacji funkcji straty.
+    ///
+    /// # Zwraca
+    ///
+    /// Przebieg najmniejszej straty (w przeskalowanej przestrzeni).
+    pub(crate) fn fit_gradient_free(
+        &mut self,
+        data: &[Vec<F>],
+        targets: &[Vec<F>],
+        method: GradientFree,
+        evaluations: usize,
+    ) -> SearchTrace {
+        self.fit_scalers(data, targets);
+        let scale = |scaler: &Option<Scaler>, rows: &[Vec<F>]| match scaler {
+            Some(scaler) => scaler.transform_rows(rows),
+            None => rows.to_vec(),
+        };
+        let data = scale(&self.input_scaler, data);
+        let targets = scale(&self.target_scaler, targets);
+
+        let x0: Vec<f64> = cast(&self.parameters());
+        let seed = self.rng.gen();
+        let mut loss = |parameters: &[f64]| {
+            self.set_parameters(&cast(parameters));
+            self.evaluate_loss(&data, &targets).as_f64()
+        };
+        let (best, _, trace) = minimize(method, &mut loss, &x0, evaluations, seed);
+        self.set_parameters(&cast(&best));
+        trace
+    }
+}
+
+/// Porównuje metody bezgradientowe z propagacją wsteczną przy równej liczbie ewaluacji
+/// straty na cos(x*y)*cos(2x). Krok propagacji wstecznej liczy gradient na całym zbiorze
+/// uczącym i jest liczony jako jedna ewaluacja (choć kosztuje około dwóch).
+///
+/// Przebiegi zapisywane są do pliku CSV (`method`, `evaluations`, `best_loss`).
+///
+/// # Argumenty
+///
+/// * `evaluations` - Liczba ewaluacji straty dla każdej metody.
+/// * `seed` - Ziarno generatora sieci.
+/// * `path` - Ścieżka pliku CSV z przebiegami.
+pub(crate) fn compare_gradient_free(evaluations: usize, seed: u64, path: &str) -> Result<(), csv::Error> {
+    const N: usize = 10;
+    let problem = Problem::new(TargetFunction::CosProduct, 2).unwrap();
+    let (data, targets) = problem.generate(200, RNG_SEED);
+    let (test_data, test_targets) = problem.generate(1000, TEST_RNG_SEED);
+    let wrapped: Vec<Vec<f64>> = targets.iter().map(|&t| vec![t]).collect();
+
+    let mut wtr = Writer::from_path(path)?;
+    wtr.write_record(["method", "evaluations", "best_loss"])?;
+    let mut results: Vec<(&str, f64, f64, f64)> = Vec::new();
+
+    // Propagacja wsteczna z pełnym wsadem
+    let start = Instant::now();
+    let mut mlp = MLP::new(2, N, 1, evaluations, 0.5, seed);
+    mlp.config.batch_size = data.len();
+    mlp.config.eval_every = (evaluations / 200).max(1);
+    let history = mlp.fit(&data, &targets);
+    let mut best = f64::INFINITY;
+    for (step, loss) in history.steps.iter().zip(history.train_loss.iter()) {
+        best = best.min(*loss);
+        wtr.write_record(&["backprop".to_string(), step.to_string(), best.to_string()])?;
+    }
+    results.push((
+        "backprop",
+        mlp.evaluate_loss(&data, &wrapped),
+        Loss::MSE.mean(&mlp.predict(&test_data), &test_targets),
+        start.elapsed().as_secs_f64(),
+    ));
+
+    for method in GRADIENT_FREE {
+        let start = Instant::now();
+        let mut mlp = MLP::new(2, N, 1, 0, 0.0, seed);
+        let trace = mlp.fit_gradient_free(&data, &wrapped, method, evaluations);
+        for (count, loss) in trace.evaluations.iter().zip(trace.best_loss.iter()) {
+            wtr.write_record(&[method.name().to_string(), count.to_string(), loss.to_string()])?;
+        }
+        results.push((
+            method.name(),
+            mlp.evaluate_loss(&data, &wrapped),
+            Loss::MSE.mean(&mlp.predict(&test_data), &test_targets),
+            start.elapsed().as_secs_f64(),
+        ));
+    }
+    wtr.flush()?;
+
+    println!("{:<10} {:>12} {:>12} {:>8}   ({} ewaluacji)", "Metoda", "MSE uczące", "MSE testowe", "czas [s]", evaluations);
+    for (name, train_mse, test_mse, seconds) in results {
+        println!("{:<10} {:>12.6} {:>12.6} {:>8.2}", name, train_mse, test_mse, seconds);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_free_methods_minimize_within_budget() {
+        let x0 = vec![3.0, -2.0, 1.5, 0.5, -1.0];
+        for method in GRADIENT_FREE {
+            let mut calls = 0;
+            let mut sphere = |x: &[f64]| {
+                calls += 1;
+                x.iter().map(|v| v * v).sum::<f64>()
+            };
+            let (best, loss, trace) = minimize(method, &mut sphere, &x0, 3000, 7);
+            assert_eq!(calls, 3000, "{}", method.name());
+            assert_eq!(trace.evaluations.last(), Some(&3000));
+            assert!(trace.best_loss.windows(2).all(|w| w[1] <= w[0]));
+            assert!(loss < 1e-2, "{}: {}", method.name(), loss);
+            assert_eq!(loss, best.iter().map(|v| v * v).sum::<f64>());
+        }
+
+        // Pełna macierz kowariancji: wektory własne odtwarzają macierz
+        let m = Matrix::from_rows(&[vec![4.0, 1.0, 0.5], vec![1.0, 3.0, 0.2], vec![0.5, 0.2, 1.0]]);
+        let (values, vectors) = symmetric_eigen(&m);
+        for i in 0..3 {
+            for j in 0..3 {
+                let r: f64 = (0..3).map(|k| vectors.row(i)[k] * values[k] * vectors.row(j)[k]).sum();
+                assert!((r - m.row(i)[j]).abs() < 1e-10);
+            }
+        }
+    }
+}
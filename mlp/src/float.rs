@@ -0,0 +1,218 @@
+use std::f64::consts::PI;
+use std::fmt::{Debug, Display};
+use std::iter::Sum;
+use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
+use std::time::Instant;
+
+use clap::ValueEnum;
+use csv::Writer;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::loss::Loss;
+use crate::{generate_data, generate_training_data, MLP, RNG_SEED, TEST_RNG_SEED};
+
+/// Precyzja obliczeń sieci.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, ValueEnum)]
+pub(crate) enum Precision {
+    /// Pojedyncza precyzja: o połowę mniej pamięci na wagi i dane.
+    F32,
+    /// Podwójna precyzja.
+    #[default]
+    F64,
+}
+
+impl Precision {
+    /// Krótka nazwa używana w wynikach.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Precision::F32 => "f32",
+            Precision::F64 => "f64",
+        }
+    }
+}
+
+/// Typ zmiennoprzecinkowy wag, wejść i wyjść sieci (`f32` albo `f64`).
+///
+/// Hiperparametry (współczynnik uczenia, kary, progi) oraz zapisywane historie strat
+/// pozostają w `f64` i są zamieniane na `F` w miejscu użycia.
+pub(crate) trait Float:
+    num_traits::Float
+    + Sum
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Default
+    + Debug
+    + Display
+    + Send
+    + Sync
+    + Serialize
+    + DeserializeOwned
+    + 'static
+{
+    /// Precyzja odpowiadająca typowi.
+    const PRECISION: Precision;
+
+    /// Wartość `f64` zaokrąglona do tego typu.
+    fn of(x: f64) -> Self;
+
+    /// Wartość jako `f64` (bez utraty dokładności).
+    fn as_f64(self) -> f64;
+}
+
+impl Float for f32 {
+    const PRECISION: Precision = Precision::F32;
+
+    fn of(x: f64) -> Self {
+        x as f32
+    }
+
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Float for f64 {
+    const PRECISION: Precision = Precision::F64;
+
+    fn of(x: f64) -> Self {
+        x
+    }
+
+    fn as_f64(self) -> f64 {
+        self
+    }
+}
+
+/// Zamienia wartości na inny typ zmiennoprzecinkowy.
+pub(crate) fn cast<F: Float, G: Float>(values: &[G]) -> Vec<F> {
+    values.iter().map(|v| F::of(v.as_f64())).collect()
+}
+
+/// Zamienia wiersze na inny typ zmiennoprzecinkowy.
+pub(crate) fn cast_rows<F: Float, G: Float>(rows: &[Vec<G>]) -> Vec<Vec<F>> {
+    rows.iter().map(|row| cast(row)).collect()
+}
+
+/// Uczy sieć w precyzji `F` i zwraca MSE (liczone w `f64`) na zbiorze testowym.
+///
+/// # Argumenty
+///
+/// * `N` - Liczba neuronów w warstwie ukrytej.
+/// * `learning_steps` - Liczba kroków uczenia.
+/// * `learning_rate` - Współczynnik uczenia.
+/// * `seed` - Ziarno generatora sieci.
+/// * `train` - Dane uczące i wartości docelowe.
+/// * `test` - Dane testowe i wartości docelowe.
+fn train_and_test<F: Float>(
+    N: usize,
+    learning_steps: usize,
+    learning_rate: f64,
+    seed: u64,
+    train: (&[Vec<f64>], &[f64]),
+    test: (&[Vec<f64>], &[f64]),
+) -> f64 {
+    let mut mlp = MLP::<F>::new(2, N, 1, learning_steps, learning_rate, seed);
+    mlp.fit(&cast_rows(train.0), &cast(train.1));
+    Loss::MSE.mean(&cast(&mlp.predict(&cast_rows(test.0))), test.1)
+}
+
+/// Porównuje uczenie w pojedynczej i podwójnej precyzji na standardowym zadaniu
+/// (1000 próbek cos(x*y)*cos(2x), 10 000 próbek testowych) i zapisuje wyniki do pliku CSV.
+///
+/// Dla każdej konfiguracji wypisuje średnie MSE testowe z `REPEATS` ziaren, łączny czas
+/// uczenia oraz względną różnicę MSE między `f32` a `f64`.
+///
+/// # Argumenty
+///
+/// * `seed` - Ziarno bazowe sieci.
+/// * `path` - Ścieżka pliku CSV z wynikami.
+pub(crate) fn compare_precision(seed: u64, path: &str) -> Result<(), csv::Error> {
+    const STEPS: usize = 10_000;
+    const REPEATS: u64 = 3;
+    let data = generate_data(1000, 0.0, PI, RNG_SEED);
+    let targets = generate_training_data(&data);
+    let test_data = generate_data(10_000, 0.0, PI, TEST_RNG_SEED);
+    let test_targets = generate_training_data(&test_data);
+    let (train, test) = ((data.as_slice(), targets.as_slice()), (test_data.as_slice(), test_targets.as_slice()));
+
+    let mut wtr = Writer::from_path(path)?;
+    wtr.write_record(["precision", "N", "learning_steps", "learning_rate", "test_mse", "seconds"])?;
+    println!(
+        "{:<9} {:>4} {:>8} {:>12} {:>8} {:>14}",
+        "Precyzja", "N", "lr", "MSE testowe", "czas [s]", "wzgl. różnica"
+    );
+    for N in [12, 52, 92] {
+        for learning_rate in [0.01, 0.1] {
+            let mut mse_f64 = 0.0;
+            for precision in [Precision::F64, Precision::F32] {
+                let start = Instant::now();
+                let mse = (0..REPEATS)
+                    .map(|i| match precision {
+                        Precision::F32 => train_and_test::<f32>(N, STEPS, learning_rate, seed + i, train, test),
+                        Precision::F64 => train_and_test::<f64>(N, STEPS, learning_rate, seed + i, train, test),
+                    })
+                    .sum::<f64>()
+                    / REPEATS as f64;
+                let seconds = start.elapsed().as_secs_f64();
+                if precision == Precision::F64 {
+                    mse_f64 = mse;
+                }
+                println!(
+                    "{:<9} {:>4} {:>8} {:>12.6} {:>8.2} {:>14.1e}",
+                    precision.name(),
+                    N,
+                    learning_rate,
+                    mse,
+                    seconds,
+                    (mse - mse_f64) / mse_f64
+                );
+                wtr.write_record(&[
+                    precision.name().to_string(),
+                    N.to_string(),
+                    STEPS.to_string(),
+                    learning_rate.to_string(),
+                    mse.to_string(),
+                    seconds.to_string(),
+                ])?;
+            }
+        }
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_file::read_model_file;
+    use std::fs;
+
+    #[test]
+    fn test_f32_matches_f64_accuracy() {
+        let data = generate_data(500, 0.0, PI, RNG_SEED);
+        let targets = generate_training_data(&data);
+        let test_data = generate_data(1000, 0.0, PI, TEST_RNG_SEED);
+        let test_targets = generate_training_data(&test_data);
+        let (train, test) = ((data.as_slice(), targets.as_slice()), (test_data.as_slice(), test_targets.as_slice()));
+
+        let mse_f32 = train_and_test::<f32>(20, 20_000, 0.1, 1, train, test);
+        let mse_f64 = train_and_test::<f64>(20, 20_000, 0.1, 1, train, test);
+        assert!(mse_f64 < 0.2, "{}", mse_f64);
+        assert!((mse_f32 - mse_f64).abs() < 0.05 * mse_f64, "f32 {} f64 {}", mse_f32, mse_f64);
+
+        // Plik modelu pamięta precyzję, a wagi f32 wczytane ponownie dają te same predykcje
+        let path = std::env::temp_dir().join(format!("mlp_precision_{}", std::process::id()));
+        let path = path.to_string_lossy();
+        let mut mlp = MLP::<f32>::new(2, 4, 1, 100, 0.1, 1);
+        mlp.fit(&cast_rows(&data), &cast(&targets));
+        mlp.save(&path).unwrap();
+        assert_eq!(read_model_file(&path).unwrap().precision(), Precision::F32);
+        let loaded = MLP::<f32>::load(&path).unwrap();
+        let rows: Vec<Vec<f32>> = cast_rows(&test_data);
+        assert_eq!(loaded.predict(&rows), mlp.predict(&rows));
+        fs::remove_file(&*path).unwrap();
+    }
+}
@@ -0,0 +1,277 @@
+use std::f64::consts::PI;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Distribution, Normal, StandardNormal};
+
+use crate::loss::Loss;
+use crate::{MLP, MODEL_RNG_SEED, RNG_SEED, TEST_RNG_SEED};
+
+/// Promień koła oraz wewnętrzny i zewnętrzny promień pierścienia problemu `Circles`,
+/// jako ułamki połowy szerokości dziedziny.
+const CIRCLES_RADII: [f64; 3] = [0.3, 0.5, 0.8];
+
+/// Syntetyczna funkcja docelowa do eksperymentów z aproksymacją.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum TargetFunction {
+    CosProduct, // cos(x1 * x2) * cos(2 * x1), funkcja z zadania
+    Rosenbrock, // suma 100 (x_{i+1} - x_i^2)^2 + (1 - x_i)^2
+    Himmelblau, // (x1^2 + x2 - 11)^2 + (x1 + x2^2 - 7)^2
+    Sinc,       // sin(r) / r, r = |x|
+    Xor,        // 1, gdy nieparzysta liczba współrzędnych jest ujemna
+    TwoSpirals, // 1 dla punktów na jednym z dwóch przeplecionych ramion spirali
+    Circles,    // Współśrodkowe koło (1) i pierścień (0) wokół środka dziedziny, rozdzielone przerwą
+}
+
+/// Wszystkie zarejestrowane funkcje, w kolejności wyświetlania.
+pub(crate) const TARGET_FUNCTIONS: [TargetFunction; 7] = [
+    TargetFunction::CosProduct,
+    TargetFunction::Rosenbrock,
+    TargetFunction::Himmelblau,
+    TargetFunction::Sinc,
+    TargetFunction::Xor,
+    TargetFunction::TwoSpirals,
+    TargetFunction::Circles,
+];
+
+impl TargetFunction {
+    /// Nazwa funkcji używana przy wyborze w czasie działania programu.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            TargetFunction::CosProduct => "cos",
+            TargetFunction::Rosenbrock => "rosenbrock",
+            TargetFunction::Himmelblau => "himmelblau",
+            TargetFunction::Sinc => "sinc",
+            TargetFunction::Xor => "xor",
+            TargetFunction::TwoSpirals => "spirals",
+            TargetFunction::Circles => "circles",
+        }
+    }
+
+    /// Wyszukuje funkcję po nazwie zwracanej przez `name`.
+    pub(crate) fn from_name(name: &str) -> Option<TargetFunction> {
+        TARGET_FUNCTIONS.iter().copied().find(|f| f.name() == name)
+    }
+
+    /// Domyślna dziedzina `[min, max]` dla każdej współrzędnej.
+    pub(crate) fn default_domain(&self) -> (f64, f64) {
+        match self {
+            TargetFunction::CosProduct => (0.0, PI),
+            TargetFunction::Rosenbrock => (-2.0, 2.0),
+            TargetFunction::Himmelblau => (-5.0, 5.0),
+            TargetFunction::Sinc => (-10.0, 10.0),
+            TargetFunction::Xor | TargetFunction::Circles => (-1.0, 1.0),
+            TargetFunction::TwoSpirals => (-3.0 * PI, 3.0 * PI),
+        }
+    }
+
+    /// Sprawdza, czy funkcja jest określona dla podanego wymiaru wejścia.
+    pub(crate) fn check_dim(&self, dim: usize) -> Result<(), String> {
+        let (min_dim, max_dim) = match self {
+            TargetFunction::CosProduct | TargetFunction::Himmelblau | TargetFunction::TwoSpirals => (2, 2),
+            TargetFunction::Rosenbrock => (2, usize::MAX),
+            TargetFunction::Sinc | TargetFunction::Xor | TargetFunction::Circles => (1, usize::MAX),
+        };
+        if dim < min_dim || dim > max_dim {
+            return Err(format!(
+                "funkcja '{}' nie obsługuje wymiaru wejścia {}",
+                self.name(),
+                dim
+            ));
+        }
+        Ok(())
+    }
+
+    /// Oblicza wartość funkcji w punkcie `x`.
+    ///
+    /// # Argumenty
+    ///
+    /// * `x` - Punkt wejściowy.
+    /// * `domain` - Dziedzina `[min, max]` każdej współrzędnej; od niej zależy tylko `Circles`.
+    pub(crate) fn evaluate(&self, x: &[f64], domain: (f64, f64)) -> f64 {
+        match self {
+            TargetFunction::CosProduct => f64::cos(x[0] * x[1]) * f64::cos(2.0 * x[0]),
+            TargetFunction::Rosenbrock => x
+                .windows(2)
+                .map(|w| 100.0 * (w[1] - w[0] * w[0]).powi(2) + (1.0 - w[0]).powi(2))
+                .sum(),
+            TargetFunction::Himmelblau => {
+                (x[0] * x[0] + x[1] - 11.0).powi(2) + (x[0] + x[1] * x[1] - 7.0).powi(2)
+            }
+            TargetFunction::Sinc => {
+                let r = x.iter().map(|v| v * v).sum::<f64>().sqrt();
+                if r == 0.0 {
+                    1.0
+                } else {
+                    r.sin() / r
+                }
+            }
+            TargetFunction::Xor => {
+                let negatives = x.iter().filter(|&&v| v < 0.0).count();
+                (negatives % 2) as f64
+            }
+            TargetFunction::TwoSpirals => {
+                // Ramiona r = theta + 2 pi k oraz r = theta + pi + 2 pi k
+                let r = (x[0] * x[0] + x[1] * x[1]).sqrt();
+                let theta = x[1].atan2(x[0]).rem_euclid(2.0 * PI);
+                let phase = ((r - theta) / (2.0 * PI)).rem_euclid(1.0);
+                (phase < 0.5) as usize as f64
+            }
+            TargetFunction::Circles => {
+                // Próbki pochodzą tylko z koła i pierścienia (`sample_circles`); punkty w przerwie
+                // i poza pierścieniem dostają etykietę bliższego z nich
+                let (min, max) = domain;
+                let (centre, half) = (0.5 * (min + max), 0.5 * (max - min));
+                let [r1, r2, _] = CIRCLES_RADII.map(|r| r * half);
+                let r = x.iter().map(|v| (v - centre).powi(2)).sum::<f64>().sqrt();
+                (r < 0.5 * (r1 + r2)) as usize as f64
+            }
+        }
+    }
+}
+
+/// Problem aproksymacji: funkcja docelowa, dziedzina, wymiar wejścia i poziom szumu.
+pub(crate) struct Problem {
+    pub(crate) function: TargetFunction,
+    pub(crate) dim: usize,
+    pub(crate) min: f64,
+    pub(crate) max: f64,
+    pub(crate) noise: f64, // Odchylenie standardowe addytywnego szumu gaussowskiego
+}
+
+impl Problem {
+    /// Tworzy problem z domyślną dziedziną funkcji i bez szumu.
+    pub(crate) fn new(function: TargetFunction, dim: usize) -> Result<Problem, String> {
+        function.check_dim(dim)?;
+        let (min, max) = function.default_domain();
+        Ok(Problem {
+            function,
+            dim,
+            min,
+            max,
+            noise: 0.0,
+        })
+    }
+
+    /// Losuje `size` punktów z dziedziny i oblicza dla nich zaszumione wartości funkcji.
+    /// Dla `Circles` punkty pochodzą tylko z koła i pierścienia.
+    ///
+    /// # Zwraca
+    ///
+    /// Krotka zawierająca punkty wejściowe oraz wartości docelowe.
+    pub(crate) fn generate(&self, size: usize, seed: u64) -> (Vec<Vec<f64>>, Vec<f64>) {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let data: Vec<Vec<f64>> = (0..size)
+            .map(|_| match self.function {
+                TargetFunction::Circles => sample_circles(&mut rng, self.dim, (self.min, self.max)),
+                _ => (0..self.dim).map(|_| rng.gen_range(self.min..=self.max)).collect(),
+            })
+            .collect();
+
+        let mut targets: Vec<f64> = data.iter().map(|x| self.function.evaluate(x, (self.min, self.max))).collect();
+        if self.noise > 0.0 {
+            let normal = Normal::new(0.0, self.noise).unwrap();
+            for t in targets.iter_mut() {
+                *t += normal.sample(&mut rng);
+            }
+        }
+
+        (data, targets)
+    }
+}
+
+/// Losuje punkt koła albo pierścienia problemu `Circles` (każde z prawdopodobieństwem 1/2):
+/// promień jednostajnie z wybranego przedziału, kierunek jednostajnie na sferze.
+fn sample_circles(rng: &mut ChaCha8Rng, dim: usize, domain: (f64, f64)) -> Vec<f64> {
+    let (min, max) = domain;
+    let (centre, half) = (0.5 * (min + max), 0.5 * (max - min));
+    let [r1, r2, r3] = CIRCLES_RADII.map(|r| r * half);
+    let radius = if rng.gen_bool(0.5) { rng.gen_range(0.0..r1) } else { rng.gen_range(r2..r3) };
+    let direction: Vec<f64> = (0..dim).map(|_| StandardNormal.sample(rng)).collect();
+    let norm = direction.iter().map(|d| d * d).sum::<f64>().sqrt().max(f64::MIN_POSITIVE);
+    direction.iter().map(|d| centre + radius * d / norm).collect()
+}
+
+/// Uczy sieć MLP na wybranym problemie i zwraca MSE na niezaszumionym zbiorze testowym.
+///
+/// # Argumenty
+///
+/// * `problem` - Problem aproksymacji.
+/// * `N` - Liczba neuronów w warstwie ukrytej
+/// * `learning_steps` - Liczba kroków uczenia
+/// * `learning_rate` - Współczynnik uczenia
+pub(crate) fn train_on_problem(problem: &Problem, N: usize, learning_steps: usize, learning_rate: f64) -> f64 {
+    let (data, targets) = problem.generate(1000, RNG_SEED);
+    let mut mlp = MLP::new(problem.dim, N, 1, learning_steps, learning_rate, MODEL_RNG_SEED);
+    mlp.fit(&data, &targets);
+
+    let test_problem = Problem { noise: 0.0, ..*problem };
+    let (test_data, test_targets) = test_problem.generate(1000, TEST_RNG_SEED);
+    Loss::MSE.mean(&mlp.predict(&test_data), &test_targets)
+}
+
+/// Porównuje jakość aproksymacji wszystkich zarejestrowanych funkcji w dwóch wymiarach.
+///
+/// # Argumenty
+///
+/// * `noise` - Odchylenie standardowe szumu dodawanego do danych uczących.
+pub(crate) fn compare_functions(noise: f64) {
+    let mut results: Vec<(&str, f64)> = Vec::new();
+    for function in TARGET_FUNCTIONS {
+        let mut problem = Problem::new(function, 2).unwrap();
+        problem.noise = noise;
+        results.push((function.name(), train_on_problem(&problem, 20, 100_000, 0.01)));
+    }
+
+    println!();
+    for (name, mse) in results {
+        println!("{:<12} MSE: {}", name, mse);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_and_known_values() {
+        for function in TARGET_FUNCTIONS {
+            assert_eq!(TargetFunction::from_name(function.name()), Some(function));
+        }
+        assert_eq!(TargetFunction::from_name("unknown"), None);
+
+        let domain = (-1.0, 1.0);
+        assert_eq!(TargetFunction::Rosenbrock.evaluate(&[1.0, 1.0, 1.0], domain), 0.0);
+        assert_eq!(TargetFunction::Himmelblau.evaluate(&[3.0, 2.0], domain), 0.0);
+        assert_eq!(TargetFunction::Sinc.evaluate(&[0.0, 0.0], domain), 1.0);
+        assert_eq!(TargetFunction::Xor.evaluate(&[-0.5, 0.5], domain), 1.0);
+        assert_eq!(TargetFunction::Xor.evaluate(&[-0.5, -0.5], domain), 0.0);
+
+        // Środek i promienie zależą od dziedziny: dla [0, 4] koło r < 0.6, pierścień 1 < r < 1.6
+        assert_eq!(TargetFunction::Circles.evaluate(&[0.0, 0.0], domain), 1.0);
+        assert_eq!(TargetFunction::Circles.evaluate(&[0.0, 0.0], (0.0, 4.0)), 0.0);
+        assert_eq!(TargetFunction::Circles.evaluate(&[2.3, 2.0], (0.0, 4.0)), 1.0);
+        assert_eq!(TargetFunction::Circles.evaluate(&[3.2, 2.0], (0.0, 4.0)), 0.0);
+        assert_eq!(TargetFunction::Circles.evaluate(&[2.0, 0.8], (0.0, 4.0)), 0.0);
+        let mut circles = Problem::new(TargetFunction::Circles, 2).unwrap();
+        (circles.min, circles.max) = (10.0, 20.0);
+        let (points, labels) = circles.generate(200, RNG_SEED);
+        assert!(labels.contains(&1.0) && labels.contains(&0.0));
+        for (x, &label) in points.iter().zip(labels.iter()) {
+            let r = ((x[0] - 15.0).powi(2) + (x[1] - 15.0).powi(2)).sqrt();
+            let expected = if label == 1.0 { 0.0..1.5 } else { 2.5..4.0 };
+            assert!(expected.contains(&r), "r = {}, etykieta {}", r, label);
+        }
+        assert!(TargetFunction::Himmelblau.check_dim(3).is_err());
+
+        let mut problem = Problem::new(TargetFunction::Sinc, 3).unwrap();
+        let (data, clean) = problem.generate(20, RNG_SEED);
+        assert!(data.iter().flatten().all(|&v| (problem.min..=problem.max).contains(&v)));
+        assert!(data.iter().all(|x| x.len() == 3));
+
+        problem.noise = 0.1;
+        let (noisy_data, noisy) = problem.generate(20, RNG_SEED);
+        assert_eq!(data, noisy_data);
+        assert_ne!(clean, noisy);
+    }
+}
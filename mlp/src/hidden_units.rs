@@ -0,0 +1,267 @@
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use csv::Writer;
+
+use crate::plots::{self, ColorScale};
+use crate::MLP;
+
+/// Nazwa rysunku map aktywacji neuronów ukrytych.
+const ACTIVATIONS_SVG: &str = "hidden_activations.svg";
+/// Nazwa rysunku map wkładów neuronów ukrytych do wyjścia.
+const CONTRIBUTIONS_SVG: &str = "hidden_contributions.svg";
+
+/// Rozkład powierzchni aproksymowanej przez sieć na wkłady neuronów ukrytych,
+/// policzony w punktach siatki `plots::grid_points`.
+///
+/// Dla wyjścia liniowego predykcja w każdym punkcie jest równa `bias + Σ_n contributions[n]`;
+/// przy skalowaniu wartości docelowych wkłady i bias są przeliczone na jednostki wyjścia.
+pub(crate) struct HiddenUnitMaps {
+    pub(crate) grid: Vec<Vec<f64>>,          // Punkty siatki
+    pub(crate) activations: Vec<Vec<f64>>,   // sigmoid(s_n) w punktach siatki, jeden wektor na neuron
+    pub(crate) contributions: Vec<Vec<f64>>, // w_n * sigmoid(s_n) w jednostkach wyjścia, jeden wektor na neuron
+    pub(crate) bias: f64,                    // Bias wyjścia w jednostkach wyjścia
+    pub(crate) output: Vec<f64>,             // Predykcja sieci w punktach siatki
+}
+
+impl HiddenUnitMaps {
+    /// Liczy aktywacje i wkłady neuronów ukrytych w punktach siatki.
+    ///
+    /// # Argumenty
+    ///
+    /// * `mlp` - Wytrenowana sieć o dwóch wejściach i liniowym pierwszym wyjściu.
+    /// * `min`, `max` - Dziedzina każdej ze współrzędnych.
+    pub(crate) fn compute(mlp: &MLP, min: f64, max: f64) -> HiddenUnitMaps {
+        let grid = plots::grid_points(min, max);
+        let hidden = mlp.hidden_activations(&grid);
+
+        // Odwrócenie skalowania wartości docelowych jest przekształceniem afinicznym a*z + b
+        let (a, b) = match &mlp.target_scaler {
+            Some(scaler) => {
+                let b = scaler.inverse_transform(&[0.0])[0];
+                (scaler.inverse_transform(&[1.0])[0] - b, b)
+            }
+            None => (1.0, 0.0),
+        };
+
+        let activations: Vec<Vec<f64>> = (0..mlp.N)
+            .map(|n| hidden.iter_rows().map(|h| h[n]).collect())
+            .collect();
+        let contributions = activations
+            .iter()
+            .zip(mlp.output_layer_weights.row(0).iter())
+            .map(|(activation, w_n)| activation.iter().map(|s| a * w_n * s).collect())
+            .collect();
+        HiddenUnitMaps {
+            output: mlp.predict(&grid),
+            grid,
+            activations,
+            contributions,
+            bias: a * mlp.output_layer_bias[0] + b,
+        }
+    }
+
+    /// Zapisuje mapy do pliku CSV z kolumnami `x1`, `x2`, `a_1..a_N` (aktywacje),
+    /// `c_1..c_N` (wkłady) i `output` (predykcja).
+    pub(crate) fn save_csv(&self, path: &str) -> Result<(), csv::Error> {
+        let N = self.activations.len();
+        let mut wtr = Writer::from_path(path)?;
+        let header = ["x1".to_string(), "x2".to_string()]
+            .into_iter()
+            .chain((1..=N).map(|n| format!("a_{}", n)))
+            .chain((1..=N).map(|n| format!("c_{}", n)))
+            .chain(std::iter::once("output".to_string()));
+        wtr.write_record(header)?;
+        for (i, x) in self.grid.iter().enumerate() {
+            let row = x
+                .iter()
+                .copied()
+                .chain(self.activations.iter().map(|a| a[i]))
+                .chain(self.contributions.iter().map(|c| c[i]))
+                .chain(std::iter::once(self.output[i]));
+            wtr.write_record(row.map(|v| v.to_string()))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Rysuje siatkę map aktywacji (skala `[0, 1]`) oraz siatkę map wkładów zakończoną
+    /// mapą ich sumy z biasem, wszystkie we wspólnej, symetrycznej skali barw.
+    ///
+    /// # Argumenty
+    ///
+    /// * `min`, `max` - Dziedzina każdej ze współrzędnych.
+    /// * `activations_path`, `contributions_path` - Ścieżki plików SVG.
+    pub(crate) fn plot(
+        &self,
+        min: f64,
+        max: f64,
+        activations_path: &str,
+        contributions_path: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let activation_maps: Vec<(String, Vec<f64>)> = self
+            .activations
+            .iter()
+            .enumerate()
+            .map(|(n, a)| (format!("neuron {}", n + 1), a.clone()))
+            .collect();
+        plots::plot_map_grid(
+            &activation_maps,
+            min,
+            max,
+            ColorScale::Sequential(0.0, 1.0),
+            "Aktywacje neuronów ukrytych sigmoid(s_n)",
+            activations_path,
+        )?;
+
+        let mut contribution_maps: Vec<(String, Vec<f64>)> = self
+            .contributions
+            .iter()
+            .enumerate()
+            .map(|(n, c)| (format!("neuron {}", n + 1), c.clone()))
+            .collect();
+        contribution_maps.push((format!("suma + bias {:.3}", self.bias), self.output.clone()));
+        let limit = contribution_maps
+            .iter()
+            .flat_map(|(_, values)| values.iter())
+            .fold(f64::MIN_POSITIVE, |m, v| m.max(v.abs()));
+        plots::plot_map_grid(
+            &contribution_maps,
+            min,
+            max,
+            ColorScale::Diverging(limit),
+            &format!("Wkłady neuronów w_n * sigmoid(s_n) (skala ±{:.3})", limit),
+            contributions_path,
+        )
+    }
+
+    /// Zapisuje stronę HTML opisującą, jak suma wkładów neuronów tworzy powierzchnię:
+    /// tabelę wag z zakresem wkładu każdego neuronu oraz rysunki z tego samego katalogu.
+    ///
+    /// # Argumenty
+    ///
+    /// * `mlp` - Sieć, z której policzono mapy.
+    /// * `path` - Ścieżka pliku HTML.
+    pub(crate) fn save_html(&self, mlp: &MLP, path: &str) -> Result<(), Box<dyn Error>> {
+        let mut rows = String::new();
+        for (n, (weights, contribution)) in mlp
+            .hidden_layer_weights
+            .iter_rows()
+            .zip(self.contributions.iter())
+            .enumerate()
+        {
+            let (low, high) = contribution
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &c| (lo.min(c), hi.max(c)));
+            writeln!(
+                rows,
+                "<tr><td>{}</td><td>{:.4}</td><td>{:.4}</td><td>{:.4}</td><td>{:.4}</td><td>[{:.4}, {:.4}]</td></tr>",
+                n + 1,
+                weights[0],
+                weights[1],
+                weights[2],
+                mlp.output_layer_weights.row(0)[n],
+                low,
+                high
+            )?;
+        }
+
+        let html = format!(
+            r#"<!DOCTYPE html>
+<html lang="pl">
+<head>
+<meta charset="utf-8">
+<title>Neurony ukryte sieci MLP</title>
+<style>
+body {{ font-family: sans-serif; max-width: 1200px; margin: auto; }}
+table {{ border-collapse: collapse; }}
+td, th {{ border: 1px solid #ccc; padding: 2px 8px; text-align: right; }}
+img {{ max-width: 100%; }}
+</style>
+</head>
+<body>
+<h1>Jak sieć tworzy powierzchnię</h1>
+<p>Każdy z {N} neuronów ukrytych liczy <code>s_n = v_n1 x1 + v_n2 x2 + v_n0</code> i przepuszcza
+wynik przez funkcję sigmoidalną, dzieląc dziedzinę łagodnym progiem wzdłuż prostej <code>s_n = 0</code>.
+Wyjście sieci to <code>y = w_0 + Σ w_n sigmoid(s_n)</code>: suma wkładów wszystkich neuronów
+i biasu (tu {bias:.4}).</p>
+<h2>Wagi neuronów</h2>
+<p>Wagi warstwy ukrytej dotyczą wejść po skalowaniu; wkłady podano w jednostkach wyjścia.</p>
+<table>
+<tr><th>n</th><th>v_n1</th><th>v_n2</th><th>v_n0 (bias)</th><th>w_n</th><th>zakres wkładu</th></tr>
+{rows}</table>
+<h2>Aktywacje sigmoid(s_n)</h2>
+<img src="{activations}" alt="Aktywacje neuronów ukrytych">
+<h2>Wkłady w_n sigmoid(s_n) i ich suma</h2>
+<img src="{contributions}" alt="Wkłady neuronów ukrytych">
+</body>
+</html>
+"#,
+            N = self.activations.len(),
+            bias = self.bias,
+            rows = rows,
+            activations = ACTIVATIONS_SVG,
+            contributions = CONTRIBUTIONS_SVG,
+        );
+        fs::write(path, html)?;
+        Ok(())
+    }
+}
+
+/// Zapisuje w katalogu `out` mapy neuronów ukrytych sieci o dwóch wejściach:
+/// `hidden_units.csv`, rysunki `hidden_activations.svg` i `hidden_contributions.svg`
+/// oraz stronę `hidden_units.html`, która je zestawia.
+///
+/// # Argumenty
+///
+/// * `mlp` - Wytrenowana sieć.
+/// * `min`, `max` - Dziedzina każdej ze współrzędnych.
+/// * `out` - Katalog plików wynikowych.
+pub(crate) fn export_hidden_units(mlp: &MLP, min: f64, max: f64, out: &Path) -> Result<(), Box<dyn Error>> {
+    let path = |name: &str| out.join(name).to_string_lossy().into_owned();
+    let maps = HiddenUnitMaps::compute(mlp, min, max);
+    maps.save_csv(&path("hidden_units.csv"))?;
+    maps.plot(min, max, &path(ACTIVATIONS_SVG), &path(CONTRIBUTIONS_SVG))?;
+    maps.save_html(mlp, &path("hidden_units.html"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaling::Scaling;
+    use crate::{generate_data, generate_training_data, RNG_SEED};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_contributions_sum_to_prediction() {
+        let data = generate_data(100, 0.0, PI, RNG_SEED);
+        let mut mlp = MLP::new(2, 5, 1, 500, 0.1, 1);
+        mlp.config.input_scaling = Scaling::Standard;
+        mlp.config.target_scaling = Scaling::MinMax;
+        mlp.fit(&data, &generate_training_data(&data));
+
+        let maps = HiddenUnitMaps::compute(&mlp, 0.0, PI);
+        assert_eq!(maps.activations.len(), 5);
+        assert!(maps.activations.iter().flatten().all(|&a| a > 0.0 && a < 1.0));
+        for (i, y) in maps.output.iter().enumerate() {
+            let sum: f64 = maps.bias + maps.contributions.iter().map(|c| c[i]).sum::<f64>();
+            assert!((sum - y).abs() < 1e-9, "{} {}", sum, y);
+        }
+
+        let out = std::env::temp_dir().join(format!("mlp_hidden_units_{}", std::process::id()));
+        fs::create_dir_all(&out).unwrap();
+        export_hidden_units(&mlp, 0.0, PI, &out).unwrap();
+        let csv = fs::read_to_string(out.join("hidden_units.csv")).unwrap();
+        assert_eq!(csv.lines().next().unwrap().split(',').count(), 2 + 2 * 5 + 1);
+        assert_eq!(csv.lines().count(), maps.grid.len() + 1);
+        let html = fs::read_to_string(out.join("hidden_units.html")).unwrap();
+        assert!(html.contains(ACTIVATIONS_SVG) && html.contains(CONTRIBUTIONS_SVG));
+        assert_eq!(html.matches("<tr><td>").count(), 5);
+        for svg in [ACTIVATIONS_SVG, CONTRIBUTIONS_SVG] {
+            assert!(fs::read_to_string(out.join(svg)).unwrap().starts_with("<svg"));
+        }
+        fs::remove_dir_all(&out).unwrap();
+    }
+}
@@ -0,0 +1,500 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Instant;
+
+use csv::Writer;
+
+use crate::float::Float;
+use crate::loss::Loss;
+use crate::matrix::{dot, norm};
+use crate::scaling::Scaler;
+use crate::{generate_data, generate_training_data, MLP, RNG_SEED, TEST_RNG_SEED};
+
+/// Stała warunku dostatecznego spadku (pierwszy warunek Wolfe'a).
+const C1: f64 = 1e-4;
+/// Liczba par (s, y) pamiętanych przez L-BFGS.
+const LBFGS_MEMORY: usize = 10;
+/// Maksymalna liczba ewaluacji w jednym przeszukiwaniu liniowym.
+const MAX_LINE_SEARCH_EVALUATIONS: usize = 30;
+/// Próg normy gradientu, poniżej którego optymalizacja jest zakończona.
+const GRADIENT_TOLERANCE: f64 = 1e-6;
+
+/// Pełnowsadowa metoda optymalizacji z przeszukiwaniem liniowym.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum LineSearchMethod {
+    Lbfgs,             // L-BFGS z dwupętlową rekursją
+    ConjugateGradient, // Nieliniowe gradienty sprzężone Polaka-Ribière'a (PR+)
+}
+
+impl LineSearchMethod {
+    /// Krótka nazwa metody używana w wynikach.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            LineSearchMethod::Lbfgs => "l-bfgs",
+            LineSearchMethod::ConjugateGradient => "cg",
+        }
+    }
+
+    /// Stała warunku krzywizny (drugi, silny warunek Wolfe'a); gradienty sprzężone
+    /// wymagają dokładniejszego przeszukiwania.
+    fn c2(&self) -> f64 {
+        match self {
+            LineSearchMethod::Lbfgs => 0.9,
+            LineSearchMethod::ConjugateGradient => 0.1,
+        }
+    }
+}
+
+/// Przyczyna zakończenia optymalizacji.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Termination {
+    GradientTolerance, // Norma gradientu poniżej progu
+    MaxIterations,     // Wykonano maksymalną liczbę iteracji
+    LineSearchFailed,  // Nie znaleziono kroku spełniającego warunki Wolfe'a
+}
+
+/// Wynik optymalizacji: liczba iteracji i ewaluacji, końcowa strata i jej przebieg
+/// (zawsze w `f64`, niezależnie od typu optymalizowanych parametrów).
+#[derive(Clone, Debug)]
+pub(crate) struct OptimizationReport {
+    pub(crate) iterations: usize,
+    pub(crate) evaluations: usize, // Liczba obliczeń straty wraz z gradientem
+    pub(crate) final_loss: f64,
+    pub(crate) termination: Termination,
+    pub(crate) loss_history: Vec<f64>, // Strata po każdej iteracji (pierwsza wartość - punkt startowy)
+}
+
+impl fmt::Display for OptimizationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "iteracje: {}, ewaluacje: {}, końcowa strata: {:.6e} ({:?})",
+            self.iterations, self.evaluations, self.final_loss, self.termination
+        )
+    }
+}
+
+/// Funkcja zwracająca wartość i gradient w punkcie.
+type LossAndGradient<'a, F> = dyn FnMut(&[F]) -> (F, Vec<F>) + 'a;
+
+/// Funkcja celu zwracająca wartość i gradient, licząca swoje wywołania.
+struct Objective<'a, F> {
+    f: &'a mut LossAndGradient<'a, F>,
+    evaluations: usize,
+}
+
+impl<F: Float> Objective<'_, F> {
+    fn evaluate(&mut self, x: &[F]) -> (F, Vec<F>) {
+        self.evaluations += 1;
+        (self.f)(x)
+    }
+}
+
+/// Punkt `x + alpha * d`.
+fn step<F: Float>(x: &[F], alpha: F, d: &[F]) -> Vec<F> {
+    x.iter().zip(d.iter()).map(|(&x, &d)| x + alpha * d).collect()
+}
+
+/// Minimum sześciennej interpolacji funkcji na przedziale `[a, b]` z wartości i pochodnych
+/// kierunkowych na jego końcach; przy braku minimum wewnątrz - środek przedziału.
+fn cubic_minimizer<F: Float>(a: (F, F, F), b: (F, F, F)) -> F {
+    let ((xa, fa, ga), (xb, fb, gb)) = (a, b);
+    let (two, three, half) = (F::of(2.0), F::of(3.0), F::of(0.5));
+    let d1 = ga + gb - three * (fa - fb) / (xa - xb);
+    let discriminant = d1 * d1 - ga * gb;
+    let (low, high) = (xa.min(xb), xa.max(xb));
+    if discriminant >= F::zero() {
+        let d2 = (xb - xa).signum() * discriminant.sqrt();
+        let x = xb - (xb - xa) * (gb + d2 - d1) / (gb - ga + two * d2);
+        // Zabezpieczenie przed punktami zbyt bliskimi końcom przedziału
+        let margin = F::of(0.1) * (high - low);
+        if x.is_finite() && x > low + margin && x < high - margin {
+            return x;
+        }
+    }
+    half * (low + high)
+}
+
+/// Przeszukiwanie liniowe spełniające silne warunki Wolfe'a (Nocedal i Wright, alg. 3.5 i 3.6).
+///
+/// # Argumenty
+///
+/// * `objective` - Funkcja celu.
+/// * `x` - Punkt początkowy.
+/// * `f0` - Wartość funkcji w `x`.
+/// * `g0` - Gradient w `x`.
+/// * `d` - Kierunek spadku.
+/// * `alpha` - Początkowa długość kroku.
+/// * `c2` - Stała warunku krzywizny.
+///
+/// # Zwraca
+///
+/// Długość kroku, nowy punkt, wartość i gradient albo `None`, gdy nie znaleziono kroku.
+/// Gdy podwajanie kroku nie wyznaczy przedziału, zwracany jest ostatni krok spełniający
+/// warunek dostatecznego spadku.
+fn wolfe_line_search<F: Float>(
+    objective: &mut Objective<F>,
+    x: &[F],
+    (f0, g0): (F, &[F]),
+    d: &[F],
+    mut alpha: F,
+    c2: f64,
+) -> Option<(F, Vec<F>, F, Vec<F>)> {
+    let (c1, c2, zero) = (F::of(C1), F::of(c2), F::zero());
+    let dg0 = dot(g0, d);
+    if dg0 >= zero {
+        return None;
+    }
+
+    // Etap 1: zwiększanie kroku aż do znalezienia przedziału zawierającego akceptowalny krok
+    let mut previous = (zero, f0, dg0);
+    let mut accepted = None;
+    let mut bracket = None;
+    for i in 0..MAX_LINE_SEARCH_EVALUATIONS {
+        let x_new = step(x, alpha, d);
+        let (f, g) = objective.evaluate(&x_new);
+        let dg = dot(&g, d);
+        if !f.is_finite() || f > f0 + c1 * alpha * dg0 || (i > 0 && f >= previous.1) {
+            bracket = Some((previous, (alpha, f, dg)));
+            break;
+        }
+        if dg.abs() <= -c2 * dg0 {
+            return Some((alpha, x_new, f, g));
+        }
+        if dg >= zero {
+            bracket = Some(((alpha, f, dg), previous));
+            break;
+        }
+        previous = (alpha, f, dg);
+        accepted = Some((alpha, x_new, f, g));
+        alpha *= F::of(2.0);
+    }
+
+    // Etap 2: zawężanie przedziału; `low` to zawsze najlepszy dotąd krok spełniający warunek spadku
+    let Some((mut low, mut high)) = bracket else {
+        return accepted;
+    };
+    for _ in 0..MAX_LINE_SEARCH_EVALUATIONS {
+        let alpha = if low.1.is_finite() && high.1.is_finite() {
+            cubic_minimizer(low, high)
+        } else {
+            F::of(0.5) * (low.0 + high.0)
+        };
+        let x_new = step(x, alpha, d);
+        let (f, g) = objective.evaluate(&x_new);
+        let dg = dot(&g, d);
+        if !f.is_finite() || f > f0 + c1 * alpha * dg0 || f >= low.1 {
+            high = (alpha, f, dg);
+        } else {
+            if dg.abs() <= -c2 * dg0 {
+                return Some((alpha, x_new, f, g));
+            }
+            if dg * (high.0 - low.0) >= zero {
+                high = low;
+            }
+            low = (alpha, f, dg);
+        }
+        // Przedział węższy niż rozdzielczość typu `F`
+        if (high.0 - low.0).abs() < F::epsilon() * low.0.abs().max(F::one()) {
+            break;
+        }
+    }
+
+    // Krok spełniający tylko warunek dostatecznego spadku jest lepszy niż żaden
+    (low.0 > zero).then(|| {
+        let x_new = step(x, low.0, d);
+        let (f, g) = objective.evaluate(&x_new);
+        (low.0, x_new, f, g)
+    })
+}
+
+/// Kierunek L-BFGS `-H g` z dwupętlowej rekursji.
+fn lbfgs_direction<F: Float>(g: &[F], memory: &VecDeque<(Vec<F>, Vec<F>, F)>) -> Vec<F> {
+    let mut q: Vec<F> = g.to_vec();
+    let mut alphas = Vec::with_capacity(memory.len());
+    for (s, y, rho) in memory.iter().rev() {
+        let a = *rho * dot(s, &q);
+        for (q, &y) in q.iter_mut().zip(y.iter()) {
+            *q -= a * y;
+        }
+        alphas.push(a);
+    }
+    // Skalowanie początkowego przybliżenia odwrotności hesjanu
+    if let Some((s, y, _)) = memory.back() {
+        let gamma = dot(s, y) / dot(y, y);
+        for q in q.iter_mut() {
+            *q *= gamma;
+        }
+    }
+    for ((s, y, rho), &a) in memory.iter().zip(alphas.iter().rev()) {
+        let b = *rho * dot(y, &q);
+        for (q, &s) in q.iter_mut().zip(s.iter()) {
+            *q += (a - b) * s;
+        }
+    }
+    q.iter().map(|&v| -v).collect()
+}
+
+/// Minimizuje funkcję różniczkowalną wybraną metodą.
+///
+/// # Argumenty
+///
+/// * `method` - Metoda optymalizacji.
+/// * `f` - Funkcja zwracająca wartość i gradient.
+/// * `x0` - Punkt początkowy.
+/// * `max_iterations` - Maksymalna liczba iteracji (przeszukiwań liniowych).
+///
+/// # Zwraca
+///
+/// Znaleziony punkt i raport z przebiegu optymalizacji.
+pub(crate) fn minimize<F: Float>(
+    method: LineSearchMethod,
+    f: &mut LossAndGradient<F>,
+    x0: &[F],
+    max_iterations: usize,
+) -> (Vec<F>, OptimizationReport) {
+    let mut objective = Objective { f, evaluations: 0 };
+    let mut x = x0.to_vec();
+    let (mut fx, mut g) = objective.evaluate(&x);
+    let mut loss_history = vec![fx.as_f64()];
+    let mut d: Vec<F> = g.iter().map(|&v| -v).collect();
+    let mut memory: VecDeque<(Vec<F>, Vec<F>, F)> = VecDeque::with_capacity(LBFGS_MEMORY);
+    let mut previous_slope: Option<(F, F)> = None; // (krok, pochodna kierunkowa) poprzedniej iteracji
+    let mut termination = Termination::MaxIterations;
+    let mut iterations = 0;
+
+    while iterations < max_iterations {
+        if norm(g.iter()).as_f64() < GRADIENT_TOLERANCE {
+            termination = Termination::GradientTolerance;
+            break;
+        }
+        // Kierunek, który nie jest kierunkiem spadku, zastępowany jest antygradientem
+        if dot(&g, &d) >= F::zero() {
+            d = g.iter().map(|&v| -v).collect();
+            memory.clear();
+            previous_slope = None;
+        }
+        let slope = dot(&g, &d);
+        let initial_step = match (method, previous_slope) {
+            (LineSearchMethod::Lbfgs, _) if !memory.is_empty() => F::one(),
+            (LineSearchMethod::ConjugateGradient, Some((alpha, previous))) => {
+                (alpha * previous / slope).min(F::of(1e3))
+            }
+            _ => F::one() / norm(g.iter()),
+        };
+
+        let Some((alpha, x_new, f_new, g_new)) =
+            wolfe_line_search(&mut objective, &x, (fx, &g), &d, initial_step, method.c2())
+        else {
+            termination = Termination::LineSearchFailed;
+            break;
+        };
+        iterations += 1;
+        previous_slope = Some((alpha, slope));
+
+        match method {
+            LineSearchMethod::Lbfgs => {
+                let s: Vec<F> = x_new.iter().zip(x.iter()).map(|(&a, &b)| a - b).collect();
+                let y: Vec<F> = g_new.iter().zip(g.iter()).map(|(&a, &b)| a - b).collect();
+                let sy = dot(&s, &y);
+                // Para bez dodatniej krzywizny zepsułaby dodatnią określoność przybliżenia
+                if sy > F::of(1e-12) {
+                    if memory.len() == LBFGS_MEMORY {
+                        memory.pop_front();
+                    }
+                    memory.push_back((s, y, F::one() / sy));
+                }
+                d = lbfgs_direction(&g_new, &memory);
+            }
+            LineSearchMethod::ConjugateGradient => {
+                let y: Vec<F> = g_new.iter().zip(g.iter()).map(|(&a, &b)| a - b).collect();
+                // Restart co `n` iteracji
+                let beta = if iterations % x.len().max(1) == 0 {
+                    F::zero()
+                } else {
+                    (dot(&g_new, &y) / dot(&g, &g)).max(F::zero())
+                };
+                d = d.iter().zip(g_new.iter()).map(|(&d, &g)| beta * d - g).collect();
+            }
+        }
+
+        x = x_new;
+        fx = f_new;
+        g = g_new;
+        loss_history.push(fx.as_f64());
+    }
+
+    let report = OptimizationReport {
+        iterations,
+        evaluations: objective.evaluations,
+        final_loss: fx.as_f64(),
+        termination,
+        loss_history,
+    };
+    (x, report)
+}
+
+impl<F: Float> MLP<F> {
+    /// Dopasowuje wagi sieci pełnowsadową metodą z przeszukiwaniem liniowym, minimalizując
+    /// stratę na całym zbiorze uczącym wraz z karami L1/L2.
+    ///
+    /// Skalery są dopasowywane tak jak w `MLP::fit_outputs`; dropout, walidacja i harmonogram
+    /// współczynnika uczenia nie są używane.
+    ///
+    /// # Argumenty
+    ///
+    /// * `data` - Dane wejściowe.
+    /// * `targets` - Wektory docelowe.
+    /// * `method` - Metoda optymalizacji.
+    /// * `max_iterations` - Maksymalna liczba iteracji.
+    ///
+    /// # Zwraca
+    ///
+    /// Raport z liczbą iteracji i ewaluacji oraz końcową stratą (w przeskalowanej przestrzeni).
+    pub(crate) fn fit_line_search(
+        &mut self,
+        data: &[Vec<F>],
+        targets: &[Vec<F>],
+        method: LineSearchMethod,
+        max_iterations: usize,
+    ) -> OptimizationReport {
+        self.fit_scalers(data, targets);
+        let scale = |scaler: &Option<Scaler>, rows: &[Vec<F>]| match scaler {
+            Some(scaler) => scaler.transform_rows(rows),
+            None => rows.to_vec(),
+        };
+        let data = scale(&self.input_scaler, data);
+        let targets = scale(&self.target_scaler, targets);
+        let inputs: Vec<&[F]> = data.iter().map(Vec::as_slice).collect();
+        let target_rows: Vec<&[F]> = targets.iter().map(Vec::as_slice).collect();
+
+        let x0 = self.parameters();
+        let mut loss_and_gradient = |parameters: &[F]| {
+            self.set_parameters(parameters);
+            let loss = self.evaluate_objective(&data, &targets) + self.penalty();
+            (loss, self.gradient(&inputs, &target_rows, None))
+        };
+        let (best, report) = minimize(method, &mut loss_and_gradient, &x0, max_iterations);
+        self.set_parameters(&best);
+        report
+    }
+}
+
+/// Porównuje L-BFGS i gradienty sprzężone z SGD z `benchmark()` na cos(x*y)*cos(2x)
+/// dla kilku rozmiarów warstwy ukrytej. Dla SGD liczba ewaluacji to liczba przetworzonych
+/// próbek podzielona przez rozmiar zbioru uczącego (równoważne pełne przejścia).
+///
+/// Wyniki zapisywane są do pliku CSV.
+///
+/// # Argumenty
+///
+/// * `max_iterations` - Maksymalna liczba iteracji metod z przeszukiwaniem liniowym.
+/// * `seed` - Ziarno generatora sieci.
+/// * `path` - Ścieżka pliku CSV z wynikami.
+pub(crate) fn compare_line_search(max_iterations: usize, seed: u64, path: &str) -> Result<(), csv::Error> {
+    const SGD_STEPS: usize = 100_000;
+    let data = generate_data(1000, 0.0, std::f64::consts::PI, RNG_SEED);
+    let targets = generate_training_data(&data);
+    let wrapped: Vec<Vec<f64>> = targets.iter().map(|&t| vec![t]).collect();
+    let test_data = generate_data(1000, 0.0, std::f64::consts::PI, TEST_RNG_SEED);
+    let test_targets = generate_training_data(&test_data);
+
+    let mut wtr = Writer::from_path(path)?;
+    wtr.write_record(["method", "N", "iterations", "evaluations", "final_loss", "test_mse", "seconds"])?;
+    println!(
+        "{:<8} {:>4} {:>10} {:>10} {:>12} {:>12} {:>8}",
+        "Metoda", "N", "iteracje", "ewaluacje", "strata", "MSE testowe", "czas [s]"
+    );
+    for N in [2, 10, 100] {
+        let mut rows: Vec<(&str, usize, usize, f64, f64, f64)> = Vec::new();
+
+        let start = Instant::now();
+        let mut mlp = MLP::new(2, N, 1, SGD_STEPS, 0.1, seed);
+        mlp.fit(&data, &targets);
+        rows.push((
+            "sgd",
+            SGD_STEPS,
+            SGD_STEPS / data.len(),
+            mlp.evaluate_objective(&data, &wrapped),
+            Loss::MSE.mean(&mlp.predict(&test_data), &test_targets),
+            start.elapsed().as_secs_f64(),
+        ));
+
+        for method in [LineSearchMethod::Lbfgs, LineSearchMethod::ConjugateGradient] {
+            let start = Instant::now();
+            let mut mlp = MLP::new(2, N, 1, 0, 0.0, seed);
+            let report = mlp.fit_line_search(&data, &wrapped, method, max_iterations);
+            rows.push((
+                method.name(),
+                report.iterations,
+                report.evaluations,
+                report.final_loss,
+                Loss::MSE.mean(&mlp.predict(&test_data), &test_targets),
+                start.elapsed().as_secs_f64(),
+            ));
+        }
+
+        for (name, iterations, evaluations, loss, test_mse, seconds) in rows {
+            println!(
+                "{:<8} {:>4} {:>10} {:>10} {:>12.6} {:>12.6} {:>8.2}",
+                name, N, iterations, evaluations, loss, test_mse, seconds
+            );
+            wtr.write_record(&[
+                name.to_string(),
+                N.to_string(),
+                iterations.to_string(),
+                evaluations.to_string(),
+                loss.to_string(),
+                test_mse.to_string(),
+                seconds.to_string(),
+            ])?;
+        }
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_search_methods_converge() {
+        // Funkcja Rosenbrocka z minimum w (1, 1)
+        let mut rosenbrock = |x: &[f64]| {
+            let (a, b) = (x[0], x[1]);
+            let f = 100.0 * (b - a * a).powi(2) + (1.0 - a).powi(2);
+            let g = vec![-400.0 * a * (b - a * a) - 2.0 * (1.0 - a), 200.0 * (b - a * a)];
+            (f, g)
+        };
+        for method in [LineSearchMethod::Lbfgs, LineSearchMethod::ConjugateGradient] {
+            let (x, report) = minimize(method, &mut rosenbrock, &[-1.2, 1.0], 1000);
+            assert_eq!(report.termination, Termination::GradientTolerance, "{}", method.name());
+            assert!((x[0] - 1.0).abs() < 1e-5 && (x[1] - 1.0).abs() < 1e-5, "{}: {:?}", method.name(), x);
+            assert!(report.evaluations >= report.iterations);
+            // Warunek dostatecznego spadku gwarantuje malejącą stratę
+            assert!(report.loss_history.windows(2).all(|w| w[1] <= w[0]));
+        }
+
+        // Na małej sieci L-BFGS w 100 iteracjach schodzi poniżej straty SGD po 20000 krokach
+        let data = generate_data(200, 0.0, std::f64::consts::PI, RNG_SEED);
+        let targets: Vec<Vec<f64>> = generate_training_data(&data).into_iter().map(|t| vec![t]).collect();
+        let mut sgd = MLP::new(2, 5, 1, 20_000, 0.1, crate::MODEL_RNG_SEED);
+        sgd.fit_outputs(&data, &targets);
+        let mut lbfgs = MLP::new(2, 5, 1, 0, 0.0, crate::MODEL_RNG_SEED);
+        let report = lbfgs.fit_line_search(&data, &targets, LineSearchMethod::Lbfgs, 100);
+        assert_eq!(report.final_loss, lbfgs.evaluate_objective(&data, &targets));
+        assert!(report.final_loss < sgd.evaluate_objective(&data, &targets));
+    }
+
+    #[test]
+    fn test_unbracketed_line_search_returns_last_sufficient_decrease() {
+        // Funkcja liniowa: warunek spadku zawsze spełniony, a warunek krzywizny nigdy
+        let mut linear = |x: &[f64]| (-x[0], vec![-1.0]);
+        let mut objective = Objective { f: &mut linear, evaluations: 0 };
+        let (alpha, x, f, g) = wolfe_line_search(&mut objective, &[0.0], (0.0, &[-1.0]), &[1.0], 1.0, 0.9).unwrap();
+        let expected = 2f64.powi(MAX_LINE_SEARCH_EVALUATIONS as i32 - 1);
+        assert_eq!((alpha, x, f, g), (expected, vec![expected], -expected, vec![-1.0]));
+        assert_eq!(objective.evaluations, MAX_LINE_SEARCH_EVALUATIONS);
+    }
+}
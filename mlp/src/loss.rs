@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+
+use crate::float::Float;
+
+/// Ograniczenie prawdopodobieństw w entropii krzyżowej, chroniące przed `ln(0)`.
+const EPS: f64 = 1e-12;
+
+/// Ograniczenie `EPS` podniesione do epsilona maszynowego typu `F`, tak żeby `1 - eps` było
+/// mniejsze od 1 także w `f32`.
+fn eps<F: Float>() -> F {
+    F::of(EPS).max(F::epsilon())
+}
+
+/// Funkcja straty, dostarczająca wartość oraz pochodną względem predykcji.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Loss {
+    MSE,                     // Błąd kwadratowy (y - t)^2; uczenie minimalizuje 1/2 (y - t)^2
+    MAE,                     // Błąd bezwzględny |y - t|
+    Huber(f64),              // Strata Hubera z progiem delta
+    LogCosh,                 // ln(cosh(y - t))
+    BinaryCrossEntropy,      // Binarna entropia krzyżowa, y w przedziale (0, 1)
+    CategoricalCrossEntropy, // Kategoryczna entropia krzyżowa, t zakodowane "one-hot"
+}
+
+impl Loss {
+    /// Wartość straty dla pojedynczej predykcji.
+    ///
+    /// # Argumenty
+    ///
+    /// * `y` - Wartość przewidziana przez model.
+    /// * `t` - Wartość docelowa.
+    pub(crate) fn value<F: Float>(&self, y: F, t: F) -> F {
+        let e = y - t;
+        let (one, half, eps) = (F::one(), F::of(0.5), eps::<F>());
+        match *self {
+            Loss::MSE => e * e,
+            Loss::MAE => e.abs(),
+            Loss::Huber(delta) => {
+                let delta = F::of(delta);
+                if e.abs() <= delta {
+                    half * e * e
+                } else {
+                    delta * (e.abs() - half * delta)
+                }
+            }
+            // ln(cosh(e)) = |e| + ln(1 + exp(-2|e|)) - ln(2), stabilne dla dużych |e|
+            Loss::LogCosh => e.abs() + (F::of(-2.0) * e.abs()).exp().ln_1p() - F::of(std::f64::consts::LN_2),
+            Loss::BinaryCrossEntropy => {
+                let y = y.max(eps).min(one - eps);
+                -(t * y.ln() + (one - t) * (one - y).ln())
+            }
+            Loss::CategoricalCrossEntropy => -t * y.max(eps).ln(),
+        }
+    }
+
+    /// Wartość minimalizowana podczas uczenia, której pochodną jest `gradient`.
+    ///
+    /// Dla `MSE` jest to 1/2 (y - t)^2, tak jak w pierwotnym SGD, którego krok
+    /// `learning_rate * (y - t)` zakładał tę skalę; domyślne współczynniki uczenia
+    /// są do niej dobrane. Dla pozostałych strat jest równa `value`.
+    ///
+    /// # Argumenty
+    ///
+    /// * `y` - Wartość przewidziana przez model.
+    /// * `t` - Wartość docelowa.
+    pub(crate) fn objective<F: Float>(&self, y: F, t: F) -> F {
+        match *self {
+            Loss::MSE => F::of(0.5) * self.value(y, t),
+            _ => self.value(y, t),
+        }
+    }
+
+    /// Pochodna funkcji celu uczenia (`objective`) względem predykcji `y`.
+    ///
+    /// # Argumenty
+    ///
+    /// * `y` - Wartość przewidziana przez model.
+    /// * `t` - Wartość docelowa.
+    pub(crate) fn gradient<F: Float>(&self, y: F, t: F) -> F {
+        let e = y - t;
+        let (one, eps) = (F::one(), eps::<F>());
+        match *self {
+            Loss::MSE => e,
+            Loss::MAE => {
+                if e == F::zero() {
+                    F::zero()
+                } else {
+                    e.signum()
+                }
+            }
+            Loss::Huber(delta) => e.max(F::of(-delta)).min(F::of(delta)),
+            Loss::LogCosh => e.tanh(),
+            Loss::BinaryCrossEntropy => {
+                let y = y.max(eps).min(one - eps);
+                (y - t) / (y * (one - y))
+            }
+            Loss::CategoricalCrossEntropy => -t / y.max(eps),
+        }
+    }
+
+    /// Średnia strata dla modelu o jednym wyjściu.
+    ///
+    /// # Argumenty
+    ///
+    /// * `predictions` - Wartości przewidziane przez model.
+    /// * `targets` - Wartości docelowe.
+    pub(crate) fn mean<F: Float>(&self, predictions: &[F], targets: &[F]) -> F {
+        predictions
+            .iter()
+            .zip(targets.iter())
+            .map(|(&y, &t)| self.value(y, t))
+            .sum::<F>()
+            / F::of(predictions.len() as f64)
+    }
+
+    /// Średnia po próbkach straty zsumowanej po wszystkich wyjściach modelu.
+    ///
+    /// # Argumenty
+    ///
+    /// * `predictions` - Wektory wyjść modelu, po jednym na próbkę.
+    /// * `targets` - Wektory docelowe, po jednym na próbkę.
+    pub(crate) fn mean_outputs<F: Float>(&self, predictions: &[Vec<F>], targets: &[Vec<F>]) -> F {
+        predictions
+            .iter()
+            .zip(targets.iter())
+            .map(|(y, t)| y.iter().zip(t.iter()).map(|(&y, &t)| self.value(y, t)).sum::<F>())
+            .sum::<F>()
+            / F::of(predictions.len() as f64)
+    }
+
+    /// Średnia po próbkach funkcji celu uczenia (`objective`) zsumowanej po wszystkich
+    /// wyjściach modelu.
+    ///
+    /// # Argumenty
+    ///
+    /// * `predictions` - Wektory wyjść modelu, po jednym na próbkę.
+    /// * `targets` - Wektory docelowe, po jednym na próbkę.
+    pub(crate) fn mean_objective<F: Float>(&self, predictions: &[Vec<F>], targets: &[Vec<F>]) -> F {
+        predictions
+            .iter()
+            .zip(targets.iter())
+            .map(|(y, t)| y.iter().zip(t.iter()).map(|(&y, &t)| self.objective(y, t)).sum::<F>())
+            .sum::<F>()
+            / F::of(predictions.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_matches_finite_difference() {
+        const H: f64 = 1e-6;
+        let losses = [
+            Loss::MSE,
+            Loss::MAE,
+            Loss::Huber(0.5),
+            Loss::LogCosh,
+            Loss::BinaryCrossEntropy,
+            Loss::CategoricalCrossEntropy,
+        ];
+        for loss in losses {
+            for (y, t) in [(0.3, 0.8), (0.9, 0.2), (0.55, 1.0), (0.05, 0.0)] {
+                let numeric = (loss.objective(y + H, t) - loss.objective(y - H, t)) / (2.0 * H);
+                let analytic = loss.gradient(y, t);
+                assert!(
+                    (numeric - analytic).abs() < 1e-4,
+                    "{:?}: y={}, t={}, numeric={}, analytic={}",
+                    loss, y, t, numeric, analytic
+                );
+            }
+        }
+        // Krok uczenia dla MSE ma pierwotną skalę learning_rate * (y - t)
+        assert_eq!(Loss::MSE.gradient(0.9, 0.2), 0.9 - 0.2);
+        assert_eq!(Loss::MSE.objective(0.9, 0.2), 0.5 * Loss::MSE.value(0.9, 0.2));
+    }
+
+    #[test]
+    fn test_cross_entropy_is_finite_for_saturated_f32() {
+        for (y, t) in [(1.0f32, 1.0f32), (1.0, 0.0), (0.0, 0.0), (0.0, 1.0)] {
+            let value = Loss::BinaryCrossEntropy.value(y, t);
+            let gradient = Loss::BinaryCrossEntropy.gradient(y, t);
+            assert!(value.is_finite() && gradient.is_finite(), "y={}, t={}: {} {}", y, t, value, gradient);
+        }
+        // Trafna saturacja kosztuje mniej niż chybiona
+        assert!(Loss::BinaryCrossEntropy.value(1.0f32, 1.0) < Loss::BinaryCrossEntropy.value(1.0f32, 0.0));
+        assert!(Loss::CategoricalCrossEntropy.value(0.0f32, 1.0).is_finite());
+    }
+}
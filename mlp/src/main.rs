@@ -3,95 +3,267 @@
 #![allow(unused_variables)]
 #![allow(clippy::upper_case_acronyms)]
 
+use clap::Parser;
 use csv::Writer;
+use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use rand_distr::{Distribution, Normal};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::f64::consts::PI;
-use std::io::{self, Write};
-use std::process::Command;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Instant;
+
+mod checkpoint;
+mod classification;
+mod cli;
+mod cross_validation;
+mod dataset;
+mod ensemble;
+mod evolution;
+mod float;
+mod functions;
+mod hidden_units;
+mod line_search;
+mod loss;
+mod matrix;
+mod model_file;
+mod plots;
+mod rbf;
+mod scaling;
+mod schedule;
+mod search;
+mod telemetry;
+mod throughput;
+mod training;
+
+use float::{cast, cast_rows, Float};
+use functions::{Problem, TargetFunction};
+use loss::Loss;
+use matrix::{dot, norm, Matrix};
+use model_file::ModelError;
+use scaling::Scaler;
+use schedule::{Schedule, Scheduler};
+use rbf::RBF;
+use search::{Model, Trial};
+use telemetry::{ProgressBar, TelemetryLog, TelemetryRecord};
+use training::{TrainingConfig, TrainingHistory, TrainingState};
 
 const RNG_SEED: u64 = 2137;
 const TEST_RNG_SEED: u64 = 0xB00B135;
+const MODEL_RNG_SEED: u64 = 0x5EED;
+/// Liczba próbek uczących w `benchmark()`; górne ograniczenie liczby podziałów walidacji krzyżowej.
+const BENCHMARK_SAMPLES: usize = 1000;
+
+/// Funkcja aktywacji warstwy wyjściowej.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum OutputActivation {
+    Identity, // Regresja, błąd kwadratowy
+    Sigmoid,  // Klasyfikacja binarna, binarna entropia krzyżowa
+    Softmax,  // Klasyfikacja wieloklasowa, kategoryczna entropia krzyżowa
+}
 
-struct MLP {
-    N: usize,                              // Liczba neuronów w warstawie ukrytej
-    learning_steps: usize,                 // Maksymalna liczba kroków uczenia
-    learning_rate: f64,                    // Wspolczynnik uczenia
-    hidden_layer_weights: Vec<(f64, f64, f64)>, // Wagi warstwy ukrytej (V)
-    output_layer_weights: Vec<f64>,        // Wagi warstwy wyjsciowej (W)
-    output_layer_bias: f64,                 // Waga biasu warstwy ukrytej
+/// Wspólny interfejs modeli regresji jednego wyjścia porównywanych w `benchmark()`.
+trait Regressor {
+    /// Dopasowuje model do danych wejściowych i wartości docelowych.
+    fn fit(&mut self, data: &[Vec<f64>], targets: &[f64]) -> Result<(), ModelError>;
+
+    /// Przewiduje wartości dla podanych punktów danych.
+    fn predict(&self, data: &[Vec<f64>]) -> Vec<f64>;
+}
+
+/// Sieć z jedną warstwą ukrytą; wagi, wejścia i wyjścia są typu `F` (`f32` albo `f64`).
+struct MLP<F = f64> {
+    N: usize,                               // Liczba neuronów w warstawie ukrytej
+    learning_steps: usize,                  // Maksymalna liczba kroków uczenia
+    learning_rate: f64,                     // Wspolczynnik uczenia
+    hidden_layer_weights: Matrix<F>,        // Wagi warstwy ukrytej (V), N wierszy; ostatnia waga neuronu to bias
+    output_layer_weights: Matrix<F>,        // Wagi warstwy wyjsciowej (W), jeden wiersz na wyjście
+    output_layer_bias: Vec<F>,              // Wagi biasu warstwy wyjściowej
+    output_activation: OutputActivation,    // Funkcja aktywacji warstwy wyjściowej
+    loss: Loss,                             // Minimalizowana funkcja straty
+    seed: u64,                              // Ziarno generatora, zapisywane w pliku modelu
+    config: TrainingConfig,                 // Harmonogram, walidacja i wczesne zatrzymanie
+    input_scaler: Option<Scaler>,           // Skalowanie wejść dopasowane w `fit`
+    target_scaler: Option<Scaler>,          // Skalowanie wartości docelowych dopasowane w `fit`
+    rng: ChaCha8Rng,                        // Generator losujący wagi i próbki uczące
 }
 
-impl MLP {
+impl<F: Float> MLP<F> {
 
     /// Tworzy nowy model MLP z określonymi parametrami.
     ///
+    /// Warstwa wyjściowa jest liniowa (`OutputActivation::Identity`) i uczona z `Loss::MSE`;
+    /// do klasyfikacji należy ustawić `output_activation` na `Sigmoid` albo `Softmax`
+    /// oraz `loss` na odpowiednią entropię krzyżową.
+    ///
     /// # Argumenty
     ///
+    /// * `n_inputs` - Liczba wejść sieci
     /// * `N` - Liczba neuronów w warstwie ukrytej
+    /// * `n_outputs` - Liczba wyjść sieci
     /// * `learning_steps` - Liczba kroków uczenia
     /// * `learning_rate` - Współczynnik uczenia
+    /// * `seed` - Ziarno generatora używanego przy inicjalizacji wag i losowaniu próbek.
+    ///   Ten sam `seed` i te same parametry dają identyczne wagi i predykcje; wagi sieci
+    ///   `f32` są zaokrągleniami wag sieci `f64`.
     ///
     /// # Zwraca
     ///
     /// Nowy model MLP z określonymi parametrami.
-    fn new(N: usize, learning_steps: usize, learning_rate: f64) -> MLP {
-        let mut model: MLP = MLP {
+    fn new(
+        n_inputs: usize,
+        N: usize,
+        n_outputs: usize,
+        learning_steps: usize,
+        learning_rate: f64,
+        seed: u64,
+    ) -> MLP<F> {
+        let mut model: MLP<F> = MLP {
             N,
             learning_steps,
             learning_rate,
-            output_layer_bias: 0.0,
-            hidden_layer_weights: Vec::with_capacity(N),
-            output_layer_weights: Vec::with_capacity(N),
+            hidden_layer_weights: Matrix::zeros(N, n_inputs + 1),
+            output_layer_weights: Matrix::zeros(n_outputs, N),
+            output_layer_bias: Vec::with_capacity(n_outputs),
+            output_activation: OutputActivation::Identity,
+            loss: Loss::MSE,
+            seed,
+            config: TrainingConfig::default(),
+            input_scaler: None,
+            target_scaler: None,
+            rng: ChaCha8Rng::seed_from_u64(seed),
         };
 
         let normal = Normal::new(0.0, 0.1).unwrap();
-        model.output_layer_bias = normal.sample(&mut rand::thread_rng());
-        for _ in 0..N {
-            // Generowanie wag dla pojedynczego neuronu w warstwie ukrytej.
-            let hidden_weight = (
-                normal.sample(&mut rand::thread_rng()),
-                normal.sample(&mut rand::thread_rng()),
-                normal.sample(&mut rand::thread_rng()),
-            );
-            // Dodanie wygenerowanych wag do listy wag dla warstwy ukrytej.
-            model.hidden_layer_weights.push(hidden_weight);
-            // Generowanie i dodanie wagi dla neuronu w warstwie wyjściowej.
-            model.output_layer_weights.push(normal.sample(&mut rand::thread_rng()));
+        for _ in 0..n_outputs {
+            model.output_layer_bias.push(F::of(normal.sample(&mut model.rng)));
+        }
+        for n in 0..N {
+            // Generowanie wag (wraz z biasem) dla pojedynczego neuronu w warstwie ukrytej.
+            for v in model.hidden_layer_weights.row_mut(n).iter_mut() {
+                *v = F::of(normal.sample(&mut model.rng));
+            }
+            // Generowanie wag łączących neuron z każdym wyjściem.
+            for k in 0..n_outputs {
+                model.output_layer_weights.row_mut(k)[n] = F::of(normal.sample(&mut model.rng));
+            }
         }
 
         model
     }
 
+    /// Liczba wejść sieci.
+    fn n_inputs(&self) -> usize {
+        self.hidden_layer_weights.cols().saturating_sub(1)
+    }
+
+    /// Liczba wyjść sieci.
+    fn n_outputs(&self) -> usize {
+        self.output_layer_bias.len()
+    }
+
     /// Propagacja w przód dla podanego wejścia i zwrócenie wyniku.
     ///
     /// # Argumenty
     ///
-    /// * `input` - Wartości wejściowe jako krotka `(f64, f64)`.
-    /// * `index` - Indeks wag warstwy ukrytej do użycia.
+    /// * `input` - Wartości wejściowe, po jednej na każde wejście sieci.
     ///
     /// # Zwraca
     ///
-    /// Krotka zawierająca wartość funkcji sigmoidalnej oraz końcową wartość wyniku.
-    fn forward_propagation(&self, input: &(f64, f64)) -> (Vec<f64>, f64) {
-        let mut y: f64 = self.output_layer_bias;
-        let mut sigmoids_si: Vec<f64> = Vec::with_capacity(self.N);
-        for n in 0..self.N {
-            // Obliczanie wartości pośredniej s_i
-            let s_i: f64 = (self.hidden_layer_weights[n].0 * input.0)
-                + (self.hidden_layer_weights[n].1 * input.1)
-                + self.hidden_layer_weights[n].2;
+    /// Krotka zawierająca wartości funkcji sigmoidalnej warstwy ukrytej oraz wartości wyjść.
+    fn forward_propagation(&self, input: &[F]) -> (Vec<F>, Vec<F>) {
+        self.forward_masked(input, None)
+    }
 
-            // Zastosowanie funkcji sigmoidalnej do wartości pośredniej s_i
-            sigmoids_si.push(self.sigmoid(s_i));
+    /// Propagacja w przód z opcjonalną maską dropoutu neuronów ukrytych.
+    ///
+    /// # Argumenty
+    ///
+    /// * `input` - Wartości wejściowe, po jednej na każde wejście sieci.
+    /// * `mask` - Mnożnik wyjścia każdego neuronu ukrytego (0 dla wyłączonych neuronów).
+    ///
+    /// # Zwraca
+    ///
+    /// Krotka zawierająca wartości funkcji sigmoidalnej warstwy ukrytej (przed nałożeniem maski)
+    /// oraz wartości wyjść.
+    fn forward_masked(&self, input: &[F], mask: Option<&[F]>) -> (Vec<F>, Vec<F>) {
+        let mut sigmoids_si: Vec<F> = vec![F::zero(); self.N];
+        let mut y: Vec<F> = vec![F::zero(); self.n_outputs()];
+        self.forward_into(input, mask, &mut sigmoids_si, &mut y);
+        (sigmoids_si, y)
+    }
 
-            // Obliczanie wartości y
-            y += self.output_layer_weights[n] * sigmoids_si[n];
+    /// Propagacja w przód jednej próbki do podanych buforów, bez alokacji.
+    ///
+    /// # Argumenty
+    ///
+    /// * `input` - Wartości wejściowe próbki.
+    /// * `mask` - Opcjonalna maska dropoutu neuronów ukrytych.
+    /// * `hidden` - Bufor na wartości funkcji sigmoidalnej warstwy ukrytej (przed maską).
+    /// * `y` - Bufor na wartości wyjść.
+    fn forward_into(&self, input: &[F], mask: Option<&[F]>, hidden: &mut [F], y: &mut [F]) {
+        // Obliczanie wartości sigmoidalnych s_i dla wszystkich neuronów
+        let n_inputs = input.len();
+        for (h_n, weights) in hidden.iter_mut().zip(self.hidden_layer_weights.iter_rows()) {
+            *h_n = self.sigmoid(dot(weights, input) + weights[n_inputs]);
         }
 
-        // Zwracanie wartości sigmoidalnej i końcowej wartości wyniku
-        (sigmoids_si, y)
+        // Obliczanie wartości y; ciągłe wiersze W czytane są sekwencyjnie
+        for (k, y_k) in y.iter_mut().enumerate() {
+            let w_k = self.output_layer_weights.row(k);
+            *y_k = self.output_layer_bias[k];
+            for n in 0..self.N {
+                *y_k += w_k[n] * (hidden[n] * mask.map_or(F::one(), |m| m[n]));
+            }
+        }
+
+        // Zastosowanie funkcji aktywacji warstwy wyjściowej
+        match self.output_activation {
+            OutputActivation::Identity => {}
+            OutputActivation::Sigmoid => {
+                for y_k in y.iter_mut() {
+                    *y_k = self.sigmoid(*y_k);
+                }
+            }
+            OutputActivation::Softmax => softmax(y),
+        }
+    }
+
+    /// Propagacja w przód całego wsadu próbek.
+    ///
+    /// Przy `config.parallel` wiersze wsadu są liczone równolegle (rayon); wynik
+    /// jest identyczny jak przy obliczeniach sekwencyjnych.
+    ///
+    /// # Argumenty
+    ///
+    /// * `inputs` - Macierz próbek, po jednym wierszu na próbkę.
+    ///
+    /// # Zwraca
+    ///
+    /// Krotka macierzy: wartości warstwy ukrytej (wiersze x N) i wartości wyjść (wiersze x wyjścia).
+    fn forward_batch(&self, inputs: &Matrix<F>) -> (Matrix<F>, Matrix<F>) {
+        let mut hidden = Matrix::zeros(inputs.rows(), self.N);
+        let mut outputs = Matrix::zeros(inputs.rows(), self.n_outputs());
+        // Wiersze brane po indeksie, żeby sieć bez neuronów ukrytych (N = 0) też dostała wyjścia
+        let rows: Vec<_> = hidden
+            .rows_mut()
+            .into_iter()
+            .zip(outputs.rows_mut())
+            .zip(inputs.iter_rows())
+            .collect();
+        if self.config.parallel {
+            rows.into_par_iter().for_each(|((h, y), x)| self.forward_into(x, None, h, y));
+        } else {
+            for ((h, y), x) in rows {
+                self.forward_into(x, None, h, y);
+            }
+        }
+        (hidden, outputs)
     }
 
     /// Oblicza funkcję sigmoidalną dla danego wejścia.
@@ -103,70 +275,570 @@ impl MLP {
     /// # Zwraca
     ///
     /// Wartość sigmoidalna dla danego wejścia.
-    fn sigmoid(&self, x: f64) -> f64 {
-        1.0 / (1.0 + f64::exp(-x))
+    fn sigmoid(&self, x: F) -> F {
+        F::one() / (F::one() + (-x).exp())
     }
 
-    /// Dopasowuje model sieci neuronowej do danych treningowych.
+    /// Dopasowuje model sieci neuronowej do danych treningowych (regresja jednego wyjścia).
     ///
     /// # Argumenty
     ///
-    /// * `data` - Wycinek krotek reprezentujących dane wejściowe.
+    /// * `data` - Wycinek wektorów reprezentujących dane wejściowe.
     /// * `training_data` - Wycinek wartości docelowych dla danych treningowych.
-    fn fit(&mut self, data: &[(f64, f64)], training_data: &[f64]) {
+    ///
+    /// # Zwraca
+    ///
+    /// Historię strat uczenia i walidacji.
+    fn fit(&mut self, data: &[Vec<F>], training_data: &[F]) -> TrainingHistory {
+        let targets: Vec<Vec<F>> = training_data.iter().map(|&t| vec![t]).collect();
+        self.fit_outputs(data, &targets)
+    }
+
+    /// Dopasowuje model sieci neuronowej do wektorów docelowych, po jednej wartości na wyjście.
+    ///
+    /// # Argumenty
+    ///
+    /// * `data` - Wycinek wektorów reprezentujących dane wejściowe.
+    /// * `targets` - Wycinek wektorów docelowych, np. zakodowanych przez `encode_labels`.
+    ///
+    /// Współczynnik uczenia zmienia się zgodnie z `config.schedule`.
+    /// Gdy `config.validation_split > 0`, część próbek jest odkładana do walidacji; uczenie
+    /// kończy się po `config.patience` ocenach bez poprawy straty walidacyjnej, a na koniec
+    /// przywracane są najlepsze wagi (`config.restore_best_weights`).
+    ///
+    /// Skalery wejść (`config.input_scaling`) i wartości docelowych (`config.target_scaling`,
+    /// tylko dla `OutputActivation::Identity`) są dopasowywane wyłącznie do części uczącej,
+    /// a straty w historii liczone są w przeskalowanej przestrzeni.
+    ///
+    /// # Zwraca
+    ///
+    /// Historię strat uczenia i walidacji.
+    fn fit_outputs(&mut self, data: &[Vec<F>], targets: &[Vec<F>]) -> TrainingHistory {
+        // Wydzielenie zbioru walidacyjnego z przetasowanych próbek
+        let mut indices: Vec<usize> = (0..data.len()).collect();
+        if self.config.validation_split > 0.0 {
+            indices.shuffle(&mut self.rng);
+        }
+        let validation_len = (self.config.validation_split * data.len() as f64) as usize;
+        let (train_data, train_targets) = select_rows(data, targets, &indices[validation_len..]);
+
+        self.fit_scalers(&train_data, &train_targets);
+
+        let scheduler = Scheduler::new(self.config.schedule.clone(), self.config.warmup_steps);
+        self.train(data, targets, TrainingState::new(indices, scheduler))
+    }
+
+    /// Dopasowuje skalery wejść i wartości docelowych (tylko dla `OutputActivation::Identity`)
+    /// do zbioru uczącego zgodnie z `config.input_scaling` i `config.target_scaling`.
+    fn fit_scalers(&mut self, train_data: &[Vec<F>], train_targets: &[Vec<F>]) {
+        self.input_scaler = Scaler::fit(self.config.input_scaling, train_data);
+        self.target_scaler = match self.output_activation {
+            OutputActivation::Identity => Scaler::fit(self.config.target_scaling, train_targets),
+            _ => None,
+        };
+    }
+
+    /// Uczy sieć od stanu `state` do `learning_steps` kroków.
+    ///
+    /// Co `config.checkpoint_every` kroków stan uczenia jest zapisywany do
+    /// `config.checkpoint_path`, skąd można go wznowić przez `MLP::resume`.
+    ///
+    /// # Argumenty
+    ///
+    /// * `data` - Wszystkie dane wejściowe (uczące i walidacyjne).
+    /// * `targets` - Wektory docelowe dla `data`.
+    /// * `state` - Stan uczenia: wykonane kroki, podział próbek, harmonogram i historia.
+    ///
+    /// # Zwraca
+    ///
+    /// Historię strat uczenia i walidacji.
+    fn train(&mut self, data: &[Vec<F>], targets: &[Vec<F>], mut state: TrainingState<F>) -> TrainingHistory {
+        let validation_len = (self.config.validation_split * data.len() as f64) as usize;
+        let (validation_indices, train_indices) = state.indices.split_at(validation_len);
+        let (train_data, train_targets) = select_rows(data, targets, train_indices);
+        let (validation_data, validation_targets) = select_rows(data, targets, validation_indices);
+
+        // Przeskalowanie obu zbiorów dopasowanymi skalerami
+        let scale = |scaler: &Option<Scaler>, rows: Vec<Vec<F>>| match scaler {
+            Some(scaler) => scaler.transform_rows(&rows),
+            None => rows,
+        };
+        let train_data = scale(&self.input_scaler, train_data);
+        let validation_data = scale(&self.input_scaler, validation_data);
+        let train_targets = scale(&self.target_scaler, train_targets);
+        let validation_targets = scale(&self.target_scaler, validation_targets);
+
+        // Telemetria i pasek postępu; wznowione uczenie dopisuje do istniejącego pliku
+        let mut telemetry = self.config.telemetry_every.and_then(|_| {
+            let path = &self.config.telemetry_path;
+            TelemetryLog::open(path, self.config.telemetry_format, state.step > 0)
+                .map_err(|e| eprintln!("Nie udało się otworzyć pliku telemetrii {}: {}", path, e))
+                .ok()
+        });
+        let progress = self.config.progress.then(|| ProgressBar::new(state.step, self.learning_steps));
+        let start = Instant::now();
+
         // Proces uczenia
-        for epoch in 0..self.learning_steps {
-            print!("Epoch {}, ", epoch);
-            io::stdout().flush().unwrap();
-            let index = rand::thread_rng().gen_range(0..data.len());
-            for n in 0..self.N {
-                let (activation_values, y) = self.forward_propagation(&data[index]);
-                // Aktualizacja wag warstwy ukrytej
-                self.hidden_layer_weights[n].0 -= self.learning_rate
-                    * (y - training_data[index])
-                    * self.output_layer_weights[n]
-                    * activation_values[n]
-                    * (1.0 - activation_values[n])
-                    * data[index].0;
-                self.hidden_layer_weights[n].1 -= self.learning_rate
-                    * (y - training_data[index])
-                    * self.output_layer_weights[n]
-                    * activation_values[n]
-                    * (1.0 - activation_values[n])
-                    * data[index].1;
-                // Aktualizacja wag warstwy wyjściowej
-                self.output_layer_weights[n] -=
-                    self.learning_rate * (y - training_data[index]) * activation_values[n];
+        for epoch in state.step..self.learning_steps {
+            // Losowanie wsadu `config.batch_size` próbek
+            let batch: Vec<usize> = (0..self.config.batch_size.max(1))
+                .map(|_| self.rng.gen_range(0..train_data.len()))
+                .collect();
+            let inputs: Vec<&[F]> = batch.iter().map(|&i| train_data[i].as_slice()).collect();
+            let batch_targets: Vec<&[F]> = batch.iter().map(|&i| train_targets[i].as_slice()).collect();
+            let learning_rate = state.scheduler.learning_rate(self.learning_rate, epoch);
+            let gradient_norm = self.train_step(&inputs, &batch_targets, learning_rate);
+
+            // Okresowa ocena na zbiorze uczącym i walidacyjnym
+            let step = epoch + 1;
+            state.step = step;
+            let mut stop = false;
+            let evaluate = step % self.config.eval_every.max(1) == 0 || step == self.learning_steps;
+            let log = self.config.telemetry_every.is_some_and(|every| step % every.max(1) == 0);
+            if evaluate || (log && telemetry.is_some()) {
+                let train_loss = self.evaluate_loss(&train_data, &train_targets).as_f64();
+                let validation_loss = (!validation_data.is_empty())
+                    .then(|| self.evaluate_loss(&validation_data, &validation_targets).as_f64());
+                if let (true, Some(log)) = (log, telemetry.as_mut()) {
+                    let record = TelemetryRecord {
+                        step,
+                        train_loss,
+                        validation_loss,
+                        gradient_norm: gradient_norm.as_f64(),
+                        hidden_weight_norm: norm(self.hidden_layer_weights.as_slice().iter()).as_f64(),
+                        output_weight_norm: norm(
+                            self.output_layer_weights.as_slice().iter().chain(self.output_layer_bias.iter()),
+                        )
+                        .as_f64(),
+                        learning_rate,
+                        elapsed_seconds: start.elapsed().as_secs_f64(),
+                    };
+                    if let Err(e) = log.write(&record) {
+                        eprintln!("Nie udało się zapisać telemetrii {}: {}", self.config.telemetry_path, e);
+                        telemetry = None;
+                    }
+                }
+                if evaluate {
+                    stop = self.record_evaluation(&mut state, learning_rate, train_loss, validation_loss);
+                }
+            }
+            if let Some(bar) = &progress {
+                bar.update(step, state.history.train_loss.last().copied());
+            }
+
+            // Zapis punktu kontrolnego
+            if self.config.checkpoint_every.is_some_and(|every| step % every.max(1) == 0) {
+                if let Err(e) = self.save_checkpoint(&state, &self.config.checkpoint_path) {
+                    eprintln!("Nie udało się zapisać punktu kontrolnego {}: {}", self.config.checkpoint_path, e);
+                }
+            }
+            if stop {
+                break;
             }
         }
+
+        if let Some(bar) = &progress {
+            bar.finish(state.step);
+        }
+
+        // Przywrócenie wag z najmniejszą stratą walidacyjną
+        if let (true, Some((_, parameters))) = (self.config.restore_best_weights, &state.best) {
+            self.set_parameters(parameters);
+        }
+
+        state.history
     }
 
-    /// Przewiduje wynik dla danego zestawu punktów danych.
+    /// Zapisuje wynik oceny modelu w historii i aktualizuje harmonogram oraz najlepsze wagi.
     ///
     /// # Argumenty
     ///
-    /// * `data` - Wektor krotek reprezentujących punkty danych wejściowych.
-    ///            Każda krotka zawiera dwie wartości typu `f64`.
+    /// * `state` - Stan uczenia.
+    /// * `learning_rate` - Współczynnik uczenia w ocenianym kroku.
+    /// * `train_loss` - Strata na zbiorze uczącym.
+    /// * `validation_loss` - Strata na zbiorze walidacyjnym, o ile go wydzielono.
     ///
     /// # Zwraca
     ///
-    /// Wektor wartości `f64` reprezentujących przewidywany wynik dla każdego punktu danych.
-    fn predict(&self, data: &[(f64, f64)]) -> Vec<f64> {
-        // Utwórz pusty wektor do przechowywania przewidywanych wartości wyjściowych
-        let mut output: Vec<f64> = Vec::new();
+    /// `true`, gdy należy zakończyć uczenie (wczesne zatrzymanie).
+    fn record_evaluation(
+        &self,
+        state: &mut TrainingState<F>,
+        learning_rate: f64,
+        train_loss: f64,
+        validation_loss: Option<f64>,
+    ) -> bool {
+        let history = &mut state.history;
+        history.steps.push(state.step);
+        history.train_loss.push(train_loss);
+        history.learning_rate.push(learning_rate);
+        let Some(validation_loss) = validation_loss else {
+            state.scheduler.observe(train_loss);
+            return false;
+        };
+        history.validation_loss.push(validation_loss);
+        state.scheduler.observe(validation_loss);
 
-        // Iteruj po każdym punkcie danych i wykonaj propagację w przód
-        for sample in data.iter() {
-            // Wywołaj metodę `forward_propagation` i dodaj drugi element zwróconej krotki do `output`
-            let (_, y) = self.forward_propagation(sample);
-            output.push(y);
+        if state.best.as_ref().is_none_or(|(best_loss, _)| validation_loss < *best_loss) {
+            state.best = Some((validation_loss, self.parameters()));
+            history.best_step = Some(state.step);
+            state.evaluations_without_improvement = 0;
+        } else {
+            state.evaluations_without_improvement += 1;
+            if self.config.patience.is_some_and(|p| state.evaluations_without_improvement >= p) {
+                history.stopped_early = true;
+                return true;
+            }
         }
+        false
+    }
 
-        // Zwróć wektor zawierający przewidywane wartości wyjściowe
-        output
+    /// Pojedynczy krok uczenia dla wsadu próbek.
+    ///
+    /// Gradient jest średnią z próbek wsadu powiększoną o kary L1/L2 z `config`, a przy
+    /// `config.dropout > 0` dla każdej próbki losowana jest maska neuronów ukrytych.
+    /// Gdy norma gradientu przekracza `config.clip_norm`, gradient jest skalowany do tej normy.
+    ///
+    /// # Argumenty
+    ///
+    /// * `inputs` - Wartości wejściowe próbek wsadu.
+    /// * `targets` - Wartości docelowe próbek wsadu.
+    /// * `learning_rate` - Współczynnik uczenia w tym kroku.
+    ///
+    /// # Zwraca
+    ///
+    /// Normę gradientu przed przycięciem.
+    fn train_step(&mut self, inputs: &[&[F]], targets: &[&[F]], learning_rate: f64) -> F {
+        // Odwrócony dropout: aktywne neurony są skalowane przez 1 / (1 - p)
+        let masks: Option<Matrix<F>> = (self.config.dropout > 0.0).then(|| {
+            let keep = 1.0 - self.config.dropout;
+            let mut masks = Matrix::zeros(inputs.len(), self.N);
+            for m in masks.as_mut_slice().iter_mut() {
+                *m = if self.rng.gen::<f64>() < keep { F::of(1.0 / keep) } else { F::zero() };
+            }
+            masks
+        });
+
+        let mut gradient = self.gradient(inputs, targets, masks.as_ref());
+        let gradient_norm = norm(gradient.iter());
+        if let Some(max_norm) = self.config.clip_norm.map(F::of) {
+            if gradient_norm > max_norm {
+                for g in gradient.iter_mut() {
+                    *g *= max_norm / gradient_norm;
+                }
+            }
+        }
+
+        for (w, g) in self
+            .hidden_layer_weights
+            .as_mut_slice()
+            .iter_mut()
+            .chain(self.output_layer_weights.as_mut_slice().iter_mut())
+            .chain(self.output_layer_bias.iter_mut())
+            .zip(gradient.iter())
+        {
+            *w -= F::of(learning_rate) * *g;
+        }
+        gradient_norm
+    }
+
+    /// Średni gradient straty (z karami L1/L2) dla wsadu próbek względem wag sieci,
+    /// i biasów, w układzie zwracanym przez `parameters`.
+    ///
+    /// Przy `config.parallel` gradienty próbek są liczone równolegle, a sumowane zawsze
+    /// w tej samej kolejności, więc wynik nie zależy od liczby wątków.
+    ///
+    /// # Argumenty
+    ///
+    /// * `inputs` - Wartości wejściowe próbek wsadu.
+    /// * `targets` - Wartości docelowe próbek wsadu.
+    /// * `masks` - Maski dropoutu neuronów ukrytych, po jednym wierszu na próbkę.
+    fn gradient(&self, inputs: &[&[F]], targets: &[&[F]], masks: Option<&Matrix<F>>) -> Vec<F> {
+        let n_inputs = self.n_inputs();
+        let len = self.N * (n_inputs + 1) + self.n_outputs() * (self.N + 1);
+        let mut gradient = vec![F::zero(); len];
+
+        if self.config.parallel && inputs.len() > 1 {
+            let mut per_sample = Matrix::zeros(inputs.len(), len);
+            per_sample
+                .as_mut_slice()
+                .par_chunks_mut(len)
+                .enumerate()
+                .for_each(|(i, g)| {
+                    let mask = masks.map(|m| m.row(i));
+                    self.accumulate_gradient(inputs[i], targets[i], mask, g);
+                });
+            for row in per_sample.iter_rows() {
+                for (g, &r) in gradient.iter_mut().zip(row.iter()) {
+                    *g += r;
+                }
+            }
+        } else {
+            for (i, (input, target)) in inputs.iter().zip(targets.iter()).enumerate() {
+                self.accumulate_gradient(input, target, masks.map(|m| m.row(i)), &mut gradient);
+            }
+        }
+        if inputs.len() > 1 {
+            for g in gradient.iter_mut() {
+                *g /= F::of(inputs.len() as f64);
+            }
+        }
+
+        // Kary `l1 * |w| + l2 * w^2 / 2` dla wag (bez biasów)
+        if self.config.l1 > 0.0 || self.config.l2 > 0.0 {
+            let (l1, l2, zero) = (F::of(self.config.l1), F::of(self.config.l2), F::zero());
+            let weights = self
+                .hidden_layer_weights
+                .iter_rows()
+                .flat_map(|row| row[..n_inputs].iter().chain(std::iter::once(&zero)))
+                .chain(self.output_layer_weights.as_slice().iter());
+            for (g, &w) in gradient.iter_mut().zip(weights) {
+                let sign = if w == zero { zero } else { w.signum() };
+                *g += l1 * sign + l2 * w;
+            }
+        }
+
+        gradient
+    }
+
+    /// Wartość kar `l1 * |w| + l2 * w^2 / 2` za wagi (bez biasów), zgodna z `MLP::gradient`.
+    fn penalty(&self) -> F {
+        if self.config.l1 == 0.0 && self.config.l2 == 0.0 {
+            return F::zero();
+        }
+        let n_inputs = self.n_inputs();
+        let (l1, half_l2) = (F::of(self.config.l1), F::of(0.5 * self.config.l2));
+        self.hidden_layer_weights
+            .iter_rows()
+            .flat_map(|row| row[..n_inputs].iter())
+            .chain(self.output_layer_weights.as_slice().iter())
+            .map(|&w| l1 * w.abs() + half_l2 * w * w)
+            .sum()
+    }
+
+    /// Dodaje gradient straty jednej próbki do `gradient` (bez kar).
+    ///
+    /// # Argumenty
+    ///
+    /// * `input` - Wartości wejściowe próbki.
+    /// * `target` - Wartości docelowe próbki.
+    /// * `mask` - Maska dropoutu neuronów ukrytych.
+    /// * `gradient` - Bufor w układzie zwracanym przez `parameters`.
+    fn accumulate_gradient(&self, input: &[F], target: &[F], mask: Option<&[F]>, gradient: &mut [F]) {
+        let n_inputs = self.n_inputs();
+        let (activation_values, y) = self.forward_masked(input, mask);
+        let errors: Vec<F> = self.output_deltas(&y, target);
+        let (hidden_gradient, output_gradient) = gradient.split_at_mut(self.N * (n_inputs + 1));
+
+        for n in 0..self.N {
+            let m_n = mask.map_or(F::one(), |m| m[n]);
+            // Błąd propagowany wstecz do neuronu n
+            let back_error: F = errors
+                .iter()
+                .enumerate()
+                .map(|(k, &e_k)| e_k * self.output_layer_weights.row(k)[n])
+                .sum::<F>()
+                * m_n
+                * activation_values[n]
+                * (F::one() - activation_values[n]);
+            // Gradient wag warstwy ukrytej i biasu neuronu (wejście stale równe 1)
+            let row = &mut hidden_gradient[n * (n_inputs + 1)..][..=n_inputs];
+            for (g_i, &x_i) in row.iter_mut().zip(input.iter()) {
+                *g_i += back_error * x_i;
+            }
+            row[n_inputs] += back_error;
+            // Gradient wag warstwy wyjściowej
+            for (k, &e_k) in errors.iter().enumerate() {
+                output_gradient[k * self.N + n] += e_k * activation_values[n] * m_n;
+            }
+        }
+        // Gradient biasów warstwy wyjściowej
+        for (g_k, &e_k) in output_gradient[errors.len() * self.N..].iter_mut().zip(errors.iter()) {
+            *g_k += e_k;
+        }
+    }
+
+    /// Średnia wartość funkcji straty modelu na danych i wartościach docelowych już
+    /// przeskalowanych przez skalery modelu.
+    fn evaluate_loss(&self, data: &[Vec<F>], targets: &[Vec<F>]) -> F {
+        self.loss.mean_outputs(&self.forward_batch(&Matrix::from_rows(data)).1.to_rows(), targets)
+    }
+
+    /// Średnia wartość funkcji celu uczenia (`Loss::objective`), zgodna z `gradient`,
+    /// na danych i wartościach docelowych już przeskalowanych przez skalery modelu.
+    fn evaluate_objective(&self, data: &[Vec<F>], targets: &[Vec<F>]) -> F {
+        self.loss.mean_objective(&self.forward_batch(&Matrix::from_rows(data)).1.to_rows(), targets)
+    }
+
+    /// Wartości wyjść sieci dla surowych danych: wejścia są skalowane, a na wyjściach
+    /// odwracane jest skalowanie wartości docelowych.
+    fn predict_outputs(&self, data: &[Vec<F>]) -> Matrix<F> {
+        let (_, mut y) = self.forward_batch(&self.scaled_inputs(data));
+        if let Some(scaler) = &self.target_scaler {
+            for i in 0..y.rows() {
+                let row = scaler.inverse_transform(y.row(i));
+                y.row_mut(i).copy_from_slice(&row);
+            }
+        }
+        y
+    }
+
+    /// Wartości funkcji sigmoidalnej `sigmoid(s_n)` każdego neuronu ukrytego dla surowych
+    /// danych (wejścia są skalowane jak w `predict`), jeden wiersz na punkt danych.
+    fn hidden_activations(&self, data: &[Vec<F>]) -> Matrix<F> {
+        self.forward_batch(&self.scaled_inputs(data)).0
+    }
+
+    /// Dane wejściowe jako macierz, przeskalowane przez skaler wejść modelu.
+    fn scaled_inputs(&self, data: &[Vec<F>]) -> Matrix<F> {
+        match &self.input_scaler {
+            Some(scaler) => Matrix::from_rows(&scaler.transform_rows(data)),
+            None => Matrix::from_rows(data),
+        }
+    }
+
+    /// Wszystkie wagi i biasy sieci jako jeden wektor: warstwa ukryta wiersz po wierszu,
+    /// następnie wagi i biasy warstwy wyjściowej.
+    fn parameters(&self) -> Vec<F> {
+        [
+            self.hidden_layer_weights.as_slice(),
+            self.output_layer_weights.as_slice(),
+            self.output_layer_bias.as_slice(),
+        ]
+        .concat()
+    }
+
+    /// Ustawia wagi i biasy sieci z wektora w układzie zwracanym przez `parameters`.
+    fn set_parameters(&mut self, parameters: &[F]) {
+        let (hidden, rest) = parameters.split_at(self.hidden_layer_weights.as_slice().len());
+        let (output, bias) = rest.split_at(self.output_layer_weights.as_slice().len());
+        self.hidden_layer_weights.as_mut_slice().copy_from_slice(hidden);
+        self.output_layer_weights.as_mut_slice().copy_from_slice(output);
+        self.output_layer_bias.copy_from_slice(bias);
+    }
+
+    /// Gradient funkcji straty względem pobudzeń warstwy wyjściowej (przed aktywacją).
+    ///
+    /// # Argumenty
+    ///
+    /// * `y` - Wartości wyjść sieci.
+    /// * `t` - Wartości docelowe.
+    fn output_deltas(&self, y: &[F], t: &[F]) -> Vec<F> {
+        match (self.output_activation, self.loss) {
+            // Entropia krzyżowa z kanoniczną aktywacją upraszcza się do y - t
+            (OutputActivation::Sigmoid, Loss::BinaryCrossEntropy)
+            | (OutputActivation::Softmax, Loss::CategoricalCrossEntropy) => {
+                y.iter().zip(t.iter()).map(|(&y_k, &t_k)| y_k - t_k).collect()
+            }
+            (OutputActivation::Identity, loss) => {
+                y.iter().zip(t.iter()).map(|(&y_k, &t_k)| loss.gradient(y_k, t_k)).collect()
+            }
+            (OutputActivation::Sigmoid, loss) => y
+                .iter()
+                .zip(t.iter())
+                .map(|(&y_k, &t_k)| loss.gradient(y_k, t_k) * y_k * (F::one() - y_k))
+                .collect(),
+            (OutputActivation::Softmax, loss) => {
+                // Iloczyn jakobianu funkcji softmax z gradientem straty
+                let g: Vec<F> = y.iter().zip(t.iter()).map(|(&y_k, &t_k)| loss.gradient(y_k, t_k)).collect();
+                let g_mean: F = dot(&g, y);
+                g.iter().zip(y.iter()).map(|(&g_k, &y_k)| y_k * (g_k - g_mean)).collect()
+            }
+        }
+    }
+
+    /// Przewiduje wynik dla danego zestawu punktów danych.
+    ///
+    /// # Argumenty
+    ///
+    /// * `data` - Wycinek wektorów reprezentujących punkty danych wejściowych.
+    ///
+    /// # Zwraca
+    ///
+    /// Wektor wartości reprezentujących przewidywany wynik (pierwsze wyjście sieci)
+    /// dla każdego punktu danych.
+    fn predict(&self, data: &[Vec<F>]) -> Vec<F> {
+        // Propagacja w przód całego zbioru jako jednego wsadu
+        let y = self.predict_outputs(data);
+
+        // Zwróć pierwsze wyjście sieci dla każdego punktu danych
+        y.iter_rows().map(|y| y[0]).collect()
+    }
+
+    /// Zwraca wartości wszystkich wyjść sieci (prawdopodobieństwa klas dla klasyfikacji).
+    ///
+    /// # Argumenty
+    ///
+    /// * `data` - Wycinek wektorów reprezentujących punkty danych wejściowych.
+    fn predict_proba(&self, data: &[Vec<F>]) -> Vec<Vec<F>> {
+        self.predict_outputs(data).to_rows()
+    }
+
+    /// Przewiduje indeksy klas dla danego zestawu punktów danych.
+    ///
+    /// Dla pojedynczego wyjścia klasa to `1`, gdy prawdopodobieństwo wynosi co najmniej 0.5,
+    /// w przeciwnym razie wybierane jest wyjście o największej wartości.
+    ///
+    /// # Argumenty
+    ///
+    /// * `data` - Wycinek wektorów reprezentujących punkty danych wejściowych.
+    fn predict_classes(&self, data: &[Vec<F>]) -> Vec<usize> {
+        self.predict_proba(data)
+            .iter()
+            .map(|y| {
+                if y.len() == 1 {
+                    (y[0] >= F::of(0.5)) as usize
+                } else {
+                    argmax(y)
+                }
+            })
+            .collect()
+    }
+}
+
+impl<F: Float> Regressor for MLP<F> {
+    fn fit(&mut self, data: &[Vec<f64>], targets: &[f64]) -> Result<(), ModelError> {
+        MLP::fit(self, &cast_rows(data), &cast(targets));
+        Ok(())
+    }
+
+    fn predict(&self, data: &[Vec<f64>]) -> Vec<f64> {
+        cast(&MLP::predict(self, &cast_rows(data)))
+    }
+}
+
+/// Zamienia wartości na prawdopodobieństwa funkcją softmax (w miejscu).
+fn softmax<F: Float>(values: &mut [F]) {
+    // Odjęcie maksimum zapobiega przepełnieniu funkcji wykładniczej
+    let max = values.iter().cloned().fold(F::neg_infinity(), F::max);
+    let mut sum = F::zero();
+    for v in values.iter_mut() {
+        *v = (*v - max).exp();
+        sum += *v;
+    }
+    for v in values.iter_mut() {
+        *v /= sum;
     }
 }
 
+/// Wybiera wiersze danych i wartości docelowych o podanych indeksach.
+fn select_rows<F: Clone>(data: &[Vec<F>], targets: &[Vec<F>], indices: &[usize]) -> (Vec<Vec<F>>, Vec<Vec<F>>) {
+    (
+        indices.iter().map(|&i| data[i].clone()).collect(),
+        indices.iter().map(|&i| targets[i].clone()).collect(),
+    )
+}
+
+/// Zwraca indeks największej wartości.
+fn argmax<F: PartialOrd>(values: &[F]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+        .map(|(i, _)| i)
+        .unwrap_or_default()
+}
+
 /// Generuje wektor losowych punktów danych w określonym zakresie.
 ///
 /// # Argumenty
@@ -178,12 +850,12 @@ impl MLP {
 ///
 /// # Zwraca
 ///
-/// Wektor krotek reprezentujących wygenerowane punkty danych.
-fn generate_data(size: usize, min: f64, max: f64, seed: u64) -> Vec<(f64, f64)> {
+/// Wektor dwuelementowych wektorów reprezentujących wygenerowane punkty danych.
+fn generate_data(size: usize, min: f64, max: f64, seed: u64) -> Vec<Vec<f64>> {
     // Inicjalizacja generatora liczb pseudolosowych z podanym ziarnem.
     let mut rng = ChaCha8Rng::seed_from_u64(seed);
     // Inicjalizacja wektora na dane wyjściowe.
-    let mut data: Vec<(f64, f64)> = Vec::new();
+    let mut data: Vec<Vec<f64>> = Vec::new();
 
     // Pętla generująca losowe punkty danych.
     for _ in 0..size {
@@ -192,7 +864,7 @@ fn generate_data(size: usize, min: f64, max: f64, seed: u64) -> Vec<(f64, f64)>
         // Generowanie losowej wartości dla y w zakresie od min do max.
         let y: f64 = rng.gen_range(min..=max);
         // Dodawanie wygenerowanego punktu do wektora danych.
-        data.push((x, y));
+        data.push(vec![x, y]);
     }
 
     // Zwracanie wektora zawierającego wygenerowane punkty danych.
@@ -203,91 +875,171 @@ fn generate_data(size: usize, min: f64, max: f64, seed: u64) -> Vec<(f64, f64)>
 ///
 /// # Argumenty
 ///
-/// * `data` - Dane wejściowe jako wektor krotek typu `(f64, f64)`.
+/// * `data` - Dane wejściowe jako wycinek dwuelementowych wektorów.
 ///
 /// # Zwraca
 ///
 /// Wektor typu `Vec<f64>` zawierający wygenerowane dane treningowe.
-fn generate_training_data(data: &Vec<(f64, f64)>) -> Vec<f64> {
+fn generate_training_data(data: &[Vec<f64>]) -> Vec<f64> {
     let mut to_return: Vec<f64> = Vec::new();
-    for (x1, x2) in data {
+    for x in data {
         // Oblicza wartość i dodaje ją do wektora to_return
-        to_return.push(f64::cos(x1 * x2) * f64::cos(2.0 * x1));
+        to_return.push(TargetFunction::CosProduct.evaluate(x, (0.0, PI)));
     }
     to_return
 }
 
-/// Uruchamia benchmark dla różnych parametrów i zapisuje wyniki do pliku CSV.
-fn benchmark() {
+/// Tworzy nienauczony model dwuwejściowy dla konfiguracji hiperparametrów.
+///
+/// # Argumenty
+///
+/// * `trial` - Konfiguracja hiperparametrów.
+/// * `seed` - Ziarno generatora modelu.
+fn build_model(trial: &Trial, seed: u64) -> Box<dyn Regressor> {
+    match trial.model {
+        Model::Mlp => Box::new(MLP::<f64>::new(2, trial.N, 1, trial.learning_steps, trial.learning_rate, seed)),
+        Model::Rbf(centres) => {
+            let mut rbf = RBF::new(2, trial.N, seed);
+            rbf.centre_selection = centres;
+            Box::new(rbf)
+        }
+    }
+}
+
+/// Uczy model dla jednej konfiguracji hiperparametrów i zwraca MSE na zbiorze testowym
+/// albo błąd dopasowania modelu.
+///
+/// # Argumenty
+///
+/// * `trial` - Konfiguracja hiperparametrów.
+/// * `seed` - Ziarno generatora modelu.
+/// * `train` - Dane uczące i wartości docelowe.
+/// * `test` - Dane testowe i wartości docelowe.
+fn evaluate_trial(
+    trial: &Trial,
+    seed: u64,
+    train: (&[Vec<f64>], &[f64]),
+    test: (&[Vec<f64>], &[f64]),
+) -> Result<f64, ModelError> {
+    let mut model = build_model(trial, seed);
+    model.fit(train.0, train.1)?;
+    Ok(Loss::MSE.mean(&model.predict(test.0), test.1))
+}
+
+/// Ocenia konfigurację hiperparametrów k-krotną walidacją krzyżową na danych uczących.
+///
+/// # Argumenty
+///
+/// * `trial` - Konfiguracja hiperparametrów.
+/// * `seed` - Ziarno podziału danych i generatora sieci.
+/// * `train` - Dane uczące i wartości docelowe.
+/// * `folds` - Liczba podziałów.
+///
+/// # Zwraca
+///
+/// Raport z miarami dla każdego podziału albo błąd dopasowania modelu.
+fn cross_validate_trial(
+    trial: &Trial,
+    seed: u64,
+    train: (&[Vec<f64>], &[f64]),
+    folds: usize,
+) -> Result<cross_validation::CrossValidationReport, ModelError> {
+    cross_validation::cross_validate(train.0, train.1, folds, 1, seed, |data, targets, validation, seed| {
+        let mut model = build_model(trial, seed);
+        model.fit(data, targets)?;
+        Ok(model.predict(validation))
+    })
+}
+
+/// Uruchamia benchmark dla siatki parametrów i zapisuje wyniki do pliku CSV.
+///
+/// Sieci MLP są porównywane z sieciami RBF o tej samej liczbie neuronów ukrytych
+/// (funkcji bazowych). Konfiguracje są oceniane równolegle, każda `REPEATS` razy
+/// z innym ziarnem modelu.
+///
+/// # Argumenty
+///
+/// * `cv_folds` - Liczba podziałów walidacji krzyżowej; `None` ocenia konfiguracje
+///   na osobnym zbiorze testowym, a `Some(k)` średnim MSE z k-krotnej walidacji
+///   krzyżowej na danych uczących (powtarzanej `REPEATS` razy z innym podziałem).
+/// * `seed` - Ziarno bazowe sieci.
+/// * `path` - Ścieżka pliku CSV z wynikami.
+fn benchmark(cv_folds: Option<usize>, seed: u64, path: &str) -> Result<(), Box<dyn Error>> {
     // Definiuje minimalne i maksymalne wartości dla parametrów
     const MIN_N: usize = 2;
     const MAX_N: usize = 100;
     const MIN_STEP: usize = 1000;
     const MAX_STEP: usize = 10000;
-    const MIN_LEARNING_RATE: f64 = 0.001;
-    const MAX_LEARNING_RATE: f64 = 0.1;
-
-    // Inicjuje wektor do przechowywania względnych błędów aproksymacji
-    let mut relative_approximation_error: Vec<(usize, usize, f64, f64)> = Vec::new();
+    const LEARNING_RATES: [f64; 3] = [0.001, 0.01, 0.1];
+    const REPEATS: usize = 3;
 
     // Generuje dane wejściowe i dane do trenowania
-    let data: Vec<(f64, f64)> = generate_data(1000, 0.0, PI, RNG_SEED);
+    let data: Vec<Vec<f64>> = generate_data(BENCHMARK_SAMPLES, 0.0, PI, RNG_SEED);
     let training_data: Vec<f64> = generate_training_data(&data);
+    let test_data: Vec<Vec<f64>> = generate_data(10_000, 0.0, PI, TEST_RNG_SEED);
+    let test_targets: Vec<f64> = generate_training_data(&test_data);
 
-    // Iteruje po wartościach parametrów
-    for N in (MIN_N..=MAX_N).step_by(10) {
-        for step in (MIN_STEP..=MAX_STEP).step_by(1000) {
-            let mut learning_rate = MIN_LEARNING_RATE;
-            while learning_rate <= MAX_LEARNING_RATE {
-                // Wyświetla bieżące wartości parametrów
-                println!("\n{} {} {}", N, step, learning_rate);
+    let N_values: Vec<usize> = (MIN_N..=MAX_N).step_by(10).collect();
+    let steps: Vec<usize> = (MIN_STEP..=MAX_STEP).step_by(1000).collect();
+    let mut trials = search::grid(&N_values, &steps, &LEARNING_RATES);
+    trials.extend(search::rbf_grid(&N_values));
 
-                // Tworzy nową sieć MLP z bieżącymi wartościami parametrów
-                let mut mlp = MLP::new(N, step, learning_rate);
-                mlp.fit(&data, &training_data);
+    let results = search::run_trials(&trials, REPEATS, seed, |trial, seed| match cv_folds {
+        Some(folds) => Ok(cross_validate_trial(trial, seed, (&data, &training_data), folds)?.mean().mse),
+        None => evaluate_trial(trial, seed, (&data, &training_data), (&test_data, &test_targets)),
+    })?;
 
-                // Dokonuje predykcji przy użyciu sieci MLP
-                let prediction = mlp.predict(&data);
-
-                let test_data: Vec<(f64, f64)> = generate_data(10_000, 0.0, std::f64::consts::PI, RNG_SEED);
-                let test_targets: Vec<f64> = generate_training_data(&test_data);
-                let test_predictions = mlp.predict(&test_data);
+    for result in results.iter() {
+        println!(
+            "{} {} {} {} MSE: {} ± {}",
+            result.trial.model.name(),
+            result.trial.N,
+            result.trial.learning_steps,
+            result.trial.learning_rate,
+            result.mean(),
+            result.std()
+        );
+    }
 
-                let mse: f64 = test_targets.iter()
-                    .zip(test_predictions.iter())
-                    .map(|(target, prediction)| (target - prediction).powi(2))
-                    .sum::<f64>() / test_targets.len() as f64;
+    // Najlepsza konfiguracja każdego modelu, ze szczegółowym raportem walidacji krzyżowej
+    println!();
+    for model in [Model::Mlp, Model::Rbf(rbf::Centres::KMeans), Model::Rbf(rbf::Centres::Random)] {
+        let best = results
+            .iter()
+            .filter(|r| r.trial.model == model)
+            .min_by(|a, b| a.mean().total_cmp(&b.mean()));
+        let Some(best) = best else { continue };
+        println!("Najlepsza konfiguracja {}: {:?}, MSE: {} ± {}", model.name(), best.trial, best.mean(), best.std());
+        if let Some(folds) = cv_folds {
+            println!("{}", cross_validate_trial(&best.trial, best.seeds[0], (&data, &training_data), folds)?);
+        }
+    }
 
-                println!("Średni błąd kwadratowy (MSE): {}", mse);
+    // Zapisuje średnie błędy do pliku CSV
+    Ok(search::save_results(&results, path)?)
+}
 
-                relative_approximation_error.push((N, step, learning_rate, mse));
+/// Losowe przeszukiwanie hiperparametrów z zapisem wyników do pliku CSV.
+///
+/// # Argumenty
+///
+/// * `count` - Liczba losowanych konfiguracji.
+/// * `seed` - Ziarno bazowe sieci.
+/// * `path` - Ścieżka pliku CSV z wynikami.
+fn random_search(count: usize, seed: u64, path: &str) -> Result<(), Box<dyn Error>> {
+    const REPEATS: usize = 3;
 
-                // // Oblicza względny błąd aproksymacji
-                // let mut sum: f64 = 0.0;
-                // for (x, y) in training_data.iter().zip(prediction.iter()) {
-                //     sum += (x - y).abs();
-                // }
-                // let relative_error: f64 = sum / training_data.len() as f64 * 100.0;
-                // relative_approximation_error.push((N, step, learning_rate, relative_error));
+    let data: Vec<Vec<f64>> = generate_data(1000, 0.0, PI, RNG_SEED);
+    let training_data: Vec<f64> = generate_training_data(&data);
+    let test_data: Vec<Vec<f64>> = generate_data(10_000, 0.0, PI, TEST_RNG_SEED);
+    let test_targets: Vec<f64> = generate_training_data(&test_data);
 
-                // Zwiększa współczynnik uczenia dziesięciokrotnie
-                learning_rate *= 10.0;
-            }
-        }
-    }
+    let trials = search::random_trials(count, (2, 100), (1000, 10000), (0.001, 0.1), RNG_SEED);
+    let results = search::run_trials(&trials, REPEATS, seed, |trial, seed| {
+        evaluate_trial(trial, seed, (&data, &training_data), (&test_data, &test_targets))
+    })?;
 
-    // Zapisuje względne błędy aproksymacji do pliku CSV
-    let mut wtr = Writer::from_path("benchmark.csv").unwrap();
-    for (N, step, learning_rate, relative_error) in relative_approximation_error {
-        wtr.write_record(&[
-            N.to_string(),
-            step.to_string(),
-            learning_rate.to_string(),
-            relative_error.to_string(),
-        ])
-        .unwrap();
-    }
-    wtr.flush().unwrap();
+    Ok(search::save_results(&results, path)?)
 }
 
 /// Generuje pliki CSV do wizualizacji danych i predykcji
@@ -297,82 +1049,166 @@ fn benchmark() {
 /// * `N` - Liczba neuronów w warstwie ukrytej
 /// * `learning_steps` - Liczba kroków uczenia
 /// * `learning_rate` - Współczynnik uczenia
-fn generate_plot_csv(N: usize, learning_steps: usize, learning_rate: f64) {
+/// * `seed` - Ziarno generatora sieci
+/// * `out` - Katalog, do którego trafiają pliki CSV, model, wykresy i mapy neuronów ukrytych
+/// * `resume` - Czy wznowić przerwane uczenie z `checkpoint.json` w katalogu `out`; punkt
+///   kontrolny musi dotyczyć sieci o tych samych parametrach
+fn generate_plot_csv(
+    N: usize,
+    learning_steps: usize,
+    learning_rate: f64,
+    seed: u64,
+    out: &Path,
+    resume: bool,
+) -> Result<(), Box<dyn Error>> {
+    let path = |name: &str| out.join(name).to_string_lossy().into_owned();
+    fs::create_dir_all(out)?;
+
     // Generowanie danych i danych treningowych
-    let data: Vec<(f64, f64)> = generate_data(1000, 0.0, PI, RNG_SEED);
+    let data: Vec<Vec<f64>> = generate_data(1000, 0.0, PI, RNG_SEED);
     let training_data: Vec<f64> = generate_training_data(&data);
 
     // Stworzenie sieci MLP z podanymi parametrami
-    let mut mlp = MLP::new(N, learning_steps, learning_rate);
+    let mut mlp = MLP::new(2, N, 1, learning_steps, learning_rate, seed);
+    // Zmniejszanie współczynnika uczenia o połowę co ćwierć uczenia
+    mlp.config.schedule = Schedule::StepDecay {
+        step_size: (learning_steps / 4).max(1),
+        gamma: 0.5,
+    };
+
+    // Punkt kontrolny co 10% uczenia; przerwane uczenie można wznowić przez `resume`
+    mlp.config.checkpoint_every = Some((learning_steps / 10).max(1));
+    mlp.config.checkpoint_path = path("checkpoint.json");
 
     // Dopasowanie sieci MLP do danych
-    mlp.fit(&data, &training_data);
+    let history = if resume {
+        // Punkt kontrolny innej sieci zastąpiłby po cichu podane parametry
+        mlp.check_checkpoint(&mlp.config.checkpoint_path)?;
+        let targets: Vec<Vec<f64>> = training_data.iter().map(|&t| vec![t]).collect();
+        let (resumed, history) = MLP::resume(&mlp.config.checkpoint_path, &data, &targets)?;
+        mlp = resumed;
+        history
+    } else {
+        mlp.fit(&data, &training_data)
+    };
+    // Uczenie zakończone, punkt kontrolny nie jest już potrzebny
+    let _ = fs::remove_file(&mlp.config.checkpoint_path);
 
-    let test_data: Vec<(f64, f64)> = generate_data(1000, 0.0, PI, RNG_SEED);
+    let test_data: Vec<Vec<f64>> = generate_data(1000, 0.0, PI, RNG_SEED);
     let test_training_data: Vec<f64> = generate_training_data(&test_data);
 
     // Wykonanie predykcji przy użyciu wytrenowanej sieci MLP
     let prediction = mlp.predict(&data);
 
     // Eksport danych do pliku CSV
-    let mut wtr = Writer::from_path("data.csv").unwrap();
-    for (x, y) in data.iter() {
-        wtr.write_record(&[x.to_string(), y.to_string()]).unwrap();
+    let mut wtr = Writer::from_path(path("data.csv"))?;
+    for x in data.iter() {
+        wtr.write_record(x.iter().map(|v| v.to_string()))?;
     }
-    wtr.flush().unwrap();
+    wtr.flush()?;
 
     // Eksport danych treningowych do pliku CSV
-    wtr = Writer::from_path("training_data.csv").unwrap();
+    wtr = Writer::from_path(path("training_data.csv"))?;
     for y in training_data.iter() {
-        wtr.write_record(&[y.to_string()]).unwrap();
+        wtr.write_record(&[y.to_string()])?;
     }
-    wtr.flush().unwrap();
+    wtr.flush()?;
 
     // Eksport predykcji do pliku CSV
-    wtr = Writer::from_path("prediction.csv").unwrap();
+    wtr = Writer::from_path(path("prediction.csv"))?;
     for y in prediction.iter() {
-        wtr.write_record(&[y.to_string()]).unwrap();
+        wtr.write_record(&[y.to_string()])?;
     }
-    wtr.flush().unwrap();
+    wtr.flush()?;
 
     // Eksport predykcji do pliku CSV
-    wtr = Writer::from_path("test_training_data.csv").unwrap();
+    wtr = Writer::from_path(path("test_training_data.csv"))?;
     for y in test_training_data.iter() {
-        wtr.write_record(&[y.to_string()]).unwrap();
+        wtr.write_record(&[y.to_string()])?;
     }
-    wtr.flush().unwrap();
+    wtr.flush()?;
 
     // Eksport wag warstwy ukrytej do pliku CSV
-    wtr = Writer::from_path("test_data.csv").unwrap();
-    for (x1, x2) in test_data.iter() {
-        wtr.write_record(&[x1.to_string(), x2.to_string()]).unwrap();
+    wtr = Writer::from_path(path("test_data.csv"))?;
+    for x in test_data.iter() {
+        wtr.write_record(x.iter().map(|v| v.to_string()))?;
     }
-    wtr.flush().unwrap();
+    wtr.flush()?;
 
-    wtr = Writer::from_path("fit_X.csv").unwrap();
-    for (x1, x2, x3) in mlp.hidden_layer_weights.iter() {
-        wtr.write_record(&[x1.to_string(), x2.to_string(), x3.to_string()]).unwrap();
+    wtr = Writer::from_path(path("fit_X.csv"))?;
+    for weights in mlp.hidden_layer_weights.iter_rows() {
+        wtr.write_record(weights.iter().map(|w| w.to_string()))?;
     }
-    wtr.flush().unwrap();
+    wtr.flush()?;
     
     // Eksport wag warstwy wyjściowej do pliku CSV
-    wtr = Writer::from_path("fit_Z.csv").unwrap();
-    for z in mlp.output_layer_weights.iter() {
-        wtr.write_record(&[z.to_string()]).unwrap();
+    wtr = Writer::from_path(path("fit_Z.csv"))?;
+    for z in mlp.output_layer_weights.row(0).iter() {
+        wtr.write_record(&[z.to_string()])?;
     }
-    wtr.flush().unwrap();
+    wtr.flush()?;
+
+    // Eksport historii uczenia do pliku CSV
+    history.save_csv(&path("history.csv"))?;
+
+    // Zapis modelu wraz ze wszystkimi biasami
+    mlp.save(&path("model.json"))?;
 
-    println!("\nCSV exported");
+    println!("CSV exported");
 
-    // Wykonanie skryptu Python do wizualizacji danych
-    let plot_script = Command::new("python3")
-        .arg("plots.py")
-        .output()
-        .expect("failed to execute process");
+    // Wykresy: powierzchnia docelowa i przewidywana, mapa błędu oraz krzywa uczenia
+    plots::plot_surfaces(&mlp, TargetFunction::CosProduct, 0.0, PI, &path("surfaces.svg"))?;
+    plots::plot_error_heatmap(&mlp, TargetFunction::CosProduct, 0.0, PI, &path("error.svg"))?;
+    plots::plot_loss_curve(&history, &path("loss.svg"))?;
+    // Mapy aktywacji i wkładów neuronów ukrytych ze stroną HTML, która je zestawia
+    hidden_units::export_hidden_units(&mlp, 0.0, PI, out)?;
+    println!("Zapisano wykresy w {}", out.display());
+    Ok(())
 }
 
-fn main() {
-    // benchmark();
+/// Porównuje krzywe uczenia dużej sieci na małym, zaszumionym zbiorze bez regularyzacji
+/// i z każdą z metod regularyzacji. Historia i wykres każdego wariantu trafiają do
+/// `regularization_<wariant>.csv` i `regularization_<wariant>.svg`.
+fn compare_regularization() -> Result<(), Box<dyn Error>> {
+    let mut problem = Problem::new(TargetFunction::CosProduct, 2)?;
+    problem.noise = 0.1;
+    let (data, targets) = problem.generate(100, RNG_SEED);
 
-    generate_plot_csv(20, 1000000, 0.1);
+    let base = TrainingConfig { validation_split: 0.3, ..TrainingConfig::default() };
+    let variants = [
+        ("none", base.clone()),
+        ("l1", TrainingConfig { l1: 1e-4, ..base.clone() }),
+        ("l2", TrainingConfig { l2: 1e-3, ..base.clone() }),
+        ("dropout", TrainingConfig { dropout: 0.2, ..base.clone() }),
+        ("clip", TrainingConfig { clip_norm: Some(1.0), ..base.clone() }),
+    ];
+    for (name, config) in variants {
+        let mut mlp = MLP::new(2, 100, 1, 200_000, 0.02, MODEL_RNG_SEED);
+        mlp.config = config;
+        let history = mlp.fit(&data, &targets);
+
+        let best = history.validation_loss.iter().cloned().fold(f64::INFINITY, f64::min);
+        println!(
+            "{}: strata uczenia {:.6}, najlepsza strata walidacyjna {:.6}",
+            name,
+            history.train_loss.last().copied().unwrap_or(f64::NAN),
+            best
+        );
+        history.save_csv(&format!("regularization_{}.csv", name))?;
+        plots::plot_loss_curve(&history, &format!("regularization_{}.svg", name))?;
+    }
+    Ok(())
 }
+
+fn main() -> ExitCode {
+    match cli::run(cli::Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Błąd: {}", e);
+            e.exit_code()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
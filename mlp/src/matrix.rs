@@ -0,0 +1,113 @@
+use crate::float::Float;
+
+/// Gęsta macierz przechowywana wierszami w jednym ciągłym wektorze.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Matrix<F = f64> {
+    rows: usize,
+    cols: usize,
+    data: Vec<F>,
+}
+
+impl<F: Float> Matrix<F> {
+    /// Macierz zerowa o podanych wymiarach.
+    pub(crate) fn zeros(rows: usize, cols: usize) -> Matrix<F> {
+        Matrix { rows, cols, data: vec![F::zero(); rows * cols] }
+    }
+
+    /// Tworzy macierz z wierszy o równej długości.
+    pub(crate) fn from_rows(rows: &[Vec<F>]) -> Matrix<F> {
+        let cols = rows.first().map_or(0, Vec::len);
+        Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.concat(),
+        }
+    }
+
+    /// Kopia macierzy jako wektor wierszy.
+    pub(crate) fn to_rows(&self) -> Vec<Vec<F>> {
+        self.iter_rows().map(<[F]>::to_vec).collect()
+    }
+
+    pub(crate) fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub(crate) fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub(crate) fn row(&self, i: usize) -> &[F] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub(crate) fn row_mut(&mut self, i: usize) -> &mut [F] {
+        &mut self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    /// Kolejne wiersze macierzy; macierz `rows x 0` ma `rows` pustych wierszy.
+    pub(crate) fn iter_rows(&self) -> impl ExactSizeIterator<Item = &[F]> + DoubleEndedIterator + Clone + '_ {
+        (0..self.rows).map(move |i| self.row(i))
+    }
+
+    /// Rozłączne wiersze macierzy do zapisu; macierz `rows x 0` ma `rows` pustych wierszy.
+    pub(crate) fn rows_mut(&mut self) -> Vec<&mut [F]> {
+        if self.cols == 0 {
+            return (0..self.rows).map(|_| &mut [][..]).collect();
+        }
+        self.data.chunks_mut(self.cols).collect()
+    }
+
+    pub(crate) fn as_slice(&self) -> &[F] {
+        &self.data
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [F] {
+        &mut self.data
+    }
+}
+
+/// Iloczyn skalarny dwóch wycinków (do długości krótszego z nich).
+pub(crate) fn dot<F: Float>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b.iter()).map(|(&x, &y)| x * y).sum()
+}
+
+/// Norma euklidesowa wartości.
+pub(crate) fn norm<'a, F: Float>(values: impl Iterator<Item = &'a F>) -> F {
+    values.map(|&v| v * v).sum::<F>().sqrt()
+}
+
+/// Rozwiązuje układ `a x = b` z macierzą symetryczną dodatnio określoną (rozkład Cholesky'ego).
+///
+/// # Zwraca
+///
+/// Rozwiązanie albo `None`, gdy macierz nie jest dodatnio określona.
+pub(crate) fn solve_positive_definite<F: Float>(a: &Matrix<F>, b: &[F]) -> Option<Vec<F>> {
+    let n = a.rows();
+    // Rozkład a = L Lᵀ
+    let mut l = Matrix::zeros(n, n);
+    for i in 0..n {
+        for j in 0..=i {
+            let sum = a.row(i)[j] - dot(&l.row(i)[..j], &l.row(j)[..j]);
+            if i == j {
+                if sum <= F::zero() || !sum.is_finite() {
+                    return None;
+                }
+                l.row_mut(i)[i] = sum.sqrt();
+            } else {
+                l.row_mut(i)[j] = sum / l.row(j)[j];
+            }
+        }
+    }
+    // L y = b, następnie Lᵀ x = y
+    let mut y = vec![F::zero(); n];
+    for i in 0..n {
+        y[i] = (b[i] - dot(&l.row(i)[..i], &y[..i])) / l.row(i)[i];
+    }
+    let mut x = vec![F::zero(); n];
+    for i in (0..n).rev() {
+        let sum: F = (i + 1..n).map(|k| l.row(k)[i] * x[k]).sum();
+        x[i] = (y[i] - sum) / l.row(i)[i];
+    }
+    Some(x)
+}
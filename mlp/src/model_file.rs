@@ -0,0 +1,322 @@
+use std::fmt;
+use std::fs;
+use std::io;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::float::{cast, cast_rows, Float, Precision};
+use crate::loss::Loss;
+use crate::matrix::Matrix;
+use crate::scaling::Scaler;
+use crate::training::TrainingConfig;
+use crate::{OutputActivation, MLP};
+
+/// Wersja formatu pliku modelu; zwiększana przy każdej niezgodnej zmianie struktury.
+const MODEL_FILE_VERSION: u32 = 1;
+
+/// Jedyna obsługiwana funkcja aktywacji warstwy ukrytej.
+const HIDDEN_ACTIVATION: &str = "sigmoid";
+
+/// Błąd zapisu lub odczytu pliku modelu.
+#[derive(Debug)]
+pub(crate) enum ModelError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    Version { found: u32, expected: u32 },
+    Shape(String),
+    Mismatch(String), // Punkt kontrolny dotyczy innych danych lub ustawień uczenia
+    Fit(String),      // Dopasowanie modelu do danych nie powiodło się
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModelError::Io(e) => write!(f, "błąd pliku modelu: {}", e),
+            ModelError::Format(e) => write!(f, "niepoprawny format pliku modelu: {}", e),
+            ModelError::Version { found, expected } => write!(
+                f,
+                "nieobsługiwana wersja pliku modelu: {} (oczekiwano {})",
+                found, expected
+            ),
+            ModelError::Shape(msg) => write!(f, "niezgodny kształt wag modelu: {}", msg),
+            ModelError::Mismatch(msg) => write!(f, "punkt kontrolny nie pasuje do uczenia: {}", msg),
+            ModelError::Fit(msg) => write!(f, "nie udało się dopasować modelu: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+impl From<io::Error> for ModelError {
+    fn from(e: io::Error) -> Self {
+        ModelError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ModelError {
+    fn from(e: serde_json::Error) -> Self {
+        ModelError::Format(e)
+    }
+}
+
+/// Architektura sieci zapisana w pliku modelu.
+#[derive(Serialize, Deserialize)]
+struct Architecture {
+    n_inputs: usize,
+    N: usize,
+    n_outputs: usize,
+    hidden_activation: String,
+    output_activation: OutputActivation,
+    #[serde(default)]
+    precision: Precision, // Precyzja, w której sieć była uczona; wagi zawsze zapisywane są jako f64
+}
+
+/// Parametry, z którymi model był uczony.
+#[derive(Serialize, Deserialize)]
+struct TrainingMetadata {
+    learning_steps: usize,
+    learning_rate: f64,
+    loss: Loss,
+    seed: u64,
+    #[serde(default)]
+    config: TrainingConfig,
+}
+
+/// Zawartość pliku modelu w formacie JSON.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ModelFile {
+    version: u32,
+    architecture: Architecture,
+    training: TrainingMetadata,
+    hidden_layer_weights: Vec<Vec<f64>>,
+    output_layer_weights: Vec<Vec<f64>>,
+    output_layer_bias: Vec<f64>,
+    #[serde(default)]
+    input_scaler: Option<Scaler>,
+    #[serde(default)]
+    target_scaler: Option<Scaler>,
+}
+
+/// Sama wersja pliku, odczytywana przed resztą zawartości.
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+impl<F: Float> MLP<F> {
+    /// Zapisuje model (architekturę, wszystkie wagi i biasy, dopasowane skalery oraz parametry
+    /// uczenia) do pliku JSON.
+    ///
+    /// # Argumenty
+    ///
+    /// * `path` - Ścieżka pliku modelu.
+    pub(crate) fn save(&self, path: &str) -> Result<(), ModelError> {
+        fs::write(path, serde_json::to_string_pretty(&self.to_model_file())?)?;
+        Ok(())
+    }
+
+    /// Wczytuje model zapisany przez `MLP::save`.
+    ///
+    /// # Argumenty
+    ///
+    /// * `path` - Ścieżka pliku modelu.
+    ///
+    /// # Zwraca
+    ///
+    /// Model dający identyczne predykcje jak zapisany (przy tej samej precyzji) albo błąd,
+    /// gdy wersja pliku lub kształt wag nie zgadzają się z architekturą.
+    pub(crate) fn load(path: &str) -> Result<MLP<F>, ModelError> {
+        MLP::from_model_file(read_model_file(path)?)
+    }
+
+    /// Zawartość pliku modelu dla tej sieci.
+    pub(crate) fn to_model_file(&self) -> ModelFile {
+        ModelFile {
+            version: MODEL_FILE_VERSION,
+            architecture: Architecture {
+                n_inputs: self.n_inputs(),
+                N: self.N,
+                n_outputs: self.n_outputs(),
+                hidden_activation: HIDDEN_ACTIVATION.to_string(),
+                output_activation: self.output_activation,
+                precision: F::PRECISION,
+            },
+            training: TrainingMetadata {
+                learning_steps: self.learning_steps,
+                learning_rate: self.learning_rate,
+                loss: self.loss,
+                seed: self.seed,
+                config: self.config.clone(),
+            },
+            hidden_layer_weights: cast_rows(&self.hidden_layer_weights.to_rows()),
+            output_layer_weights: cast_rows(&self.output_layer_weights.to_rows()),
+            output_layer_bias: cast(&self.output_layer_bias),
+            input_scaler: self.input_scaler.clone(),
+            target_scaler: self.target_scaler.clone(),
+        }
+    }
+
+    /// Odtwarza sieć z zawartości pliku modelu po sprawdzeniu wersji i kształtu wag.
+    /// Generator losowy jest inicjalizowany zapisanym ziarnem, a wagi są zamieniane na `F`.
+    pub(crate) fn from_model_file(file: ModelFile) -> Result<MLP<F>, ModelError> {
+        check_version(file.version)?;
+        file.check_shapes()?;
+
+        // Bez neuronów ukrytych liczbę wejść zachowuje tylko szerokość pustej macierzy wag
+        let hidden_layer_weights = if file.hidden_layer_weights.is_empty() {
+            Matrix::zeros(0, file.architecture.n_inputs + 1)
+        } else {
+            Matrix::from_rows(&cast_rows(&file.hidden_layer_weights))
+        };
+        Ok(MLP {
+            N: file.architecture.N,
+            learning_steps: file.training.learning_steps,
+            learning_rate: file.training.learning_rate,
+            hidden_layer_weights,
+            output_layer_weights: Matrix::from_rows(&cast_rows(&file.output_layer_weights)),
+            output_layer_bias: cast(&file.output_layer_bias),
+            output_activation: file.architecture.output_activation,
+            loss: file.training.loss,
+            seed: file.training.seed,
+            config: file.training.config,
+            input_scaler: file.input_scaler,
+            target_scaler: file.target_scaler,
+            rng: ChaCha8Rng::seed_from_u64(file.training.seed),
+        })
+    }
+}
+
+/// Wczytuje zawartość pliku modelu zapisanego przez `MLP::save`.
+///
+/// Wersja jest sprawdzana przed odczytem reszty pliku, której format mógł się zmienić.
+pub(crate) fn read_model_file(path: &str) -> Result<ModelFile, ModelError> {
+    let text = fs::read_to_string(path)?;
+    let probe: VersionProbe = serde_json::from_str(&text)?;
+    check_version(probe.version)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+/// Sprawdza, czy wersja pliku jest obsługiwana.
+fn check_version(version: u32) -> Result<(), ModelError> {
+    if version != MODEL_FILE_VERSION {
+        return Err(ModelError::Version {
+            found: version,
+            expected: MODEL_FILE_VERSION,
+        });
+    }
+    Ok(())
+}
+
+impl ModelFile {
+    /// Precyzja, w której sieć była uczona.
+    pub(crate) fn precision(&self) -> Precision {
+        self.architecture.precision
+    }
+
+    /// Sprawdza, czy wymiary wag odpowiadają zapisanej architekturze.
+    fn check_shapes(&self) -> Result<(), ModelError> {
+        let arch = &self.architecture;
+        if arch.hidden_activation != HIDDEN_ACTIVATION {
+            return Err(ModelError::Shape(format!(
+                "nieobsługiwana aktywacja warstwy ukrytej '{}'",
+                arch.hidden_activation
+            )));
+        }
+        if self.hidden_layer_weights.len() != arch.N {
+            return Err(ModelError::Shape(format!(
+                "warstwa ukryta ma {} neuronów, architektura wymaga {}",
+                self.hidden_layer_weights.len(),
+                arch.N
+            )));
+        }
+        if let Some(row) = self.hidden_layer_weights.iter().find(|w| w.len() != arch.n_inputs + 1) {
+            return Err(ModelError::Shape(format!(
+                "neuron ukryty ma {} wag, oczekiwano {} (wejścia + bias)",
+                row.len(),
+                arch.n_inputs + 1
+            )));
+        }
+        if self.output_layer_weights.len() != arch.n_outputs || self.output_layer_bias.len() != arch.n_outputs {
+            return Err(ModelError::Shape(format!(
+                "warstwa wyjściowa ma {} wierszy wag i {} biasów, architektura wymaga {} wyjść",
+                self.output_layer_weights.len(),
+                self.output_layer_bias.len(),
+                arch.n_outputs
+            )));
+        }
+        if let Some(row) = self.output_layer_weights.iter().find(|w| w.len() != arch.N) {
+            return Err(ModelError::Shape(format!(
+                "wyjście ma {} wag, oczekiwano {}",
+                row.len(),
+                arch.N
+            )));
+        }
+        for (name, scaler, expected) in [
+            ("wejść", &self.input_scaler, arch.n_inputs),
+            ("wartości docelowych", &self.target_scaler, arch.n_outputs),
+        ] {
+            if let Some(scaler) = scaler.as_ref().filter(|s| s.offset.len() != expected || s.scale.len() != expected) {
+                return Err(ModelError::Shape(format!(
+                    "skaler {} ma {} kolumn, oczekiwano {}",
+                    name,
+                    scaler.offset.len(),
+                    expected
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_data, generate_training_data, MODEL_RNG_SEED, RNG_SEED};
+    use std::f64::consts::PI;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("mlp_{}_{}.json", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_save_load_roundtrip_and_errors() {
+        let data = generate_data(50, 0.0, PI, RNG_SEED);
+        let mut mlp = MLP::new(2, 5, 1, 200, 0.1, MODEL_RNG_SEED);
+        mlp.fit(&data, &generate_training_data(&data));
+
+        let path = temp_path("roundtrip");
+        mlp.save(&path).unwrap();
+        let loaded = MLP::load(&path).unwrap();
+        let bits = |y: Vec<f64>| -> Vec<u64> { y.iter().map(|v| v.to_bits()).collect() };
+        assert_eq!(bits(mlp.predict(&data)), bits(loaded.predict(&data)));
+
+        // Niezgodna wersja pliku
+        let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        json["version"] = serde_json::json!(MODEL_FILE_VERSION + 1);
+        fs::write(&path, json.to_string()).unwrap();
+        assert!(matches!(MLP::<f64>::load(&path), Err(ModelError::Version { .. })));
+
+        // Niezgodny kształt wag
+        json["version"] = serde_json::json!(MODEL_FILE_VERSION);
+        json["architecture"]["N"] = serde_json::json!(6);
+        fs::write(&path, json.to_string()).unwrap();
+        assert!(matches!(MLP::<f64>::load(&path), Err(ModelError::Shape(_))));
+
+        // Sieć bez neuronów ukrytych ma wiersz wag wyjścia o długości 0 i też musi się wczytać
+        let empty = MLP::<f64>::new(2, 0, 1, 0, 0.1, MODEL_RNG_SEED);
+        empty.save(&path).unwrap();
+        let loaded = MLP::<f64>::load(&path).unwrap();
+        assert_eq!(loaded.output_layer_weights.rows(), 1);
+        assert_eq!(loaded.n_inputs(), 2);
+        // Wyjście sieci bez neuronów ukrytych to sam bias
+        assert_eq!(loaded.predict(&data)[0], loaded.output_layer_bias[0]);
+        assert_eq!(loaded.predict(&data), empty.predict(&data));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,359 @@
+use std::error::Error;
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use crate::ensemble::Ensemble;
+use crate::functions::TargetFunction;
+use crate::training::TrainingHistory;
+use crate::MLP;
+
+/// Liczba punktów siatki w każdym wymiarze.
+const RESOLUTION: usize = 40;
+
+/// Wartości równomiernie rozłożone na przedziale `[min, max]`.
+fn linspace(min: f64, max: f64, count: usize) -> Vec<f64> {
+    (0..count)
+        .map(|i| min + (max - min) * i as f64 / (count - 1) as f64)
+        .collect()
+}
+
+/// Najmniejsza i największa wartość w wycinku.
+fn value_range(values: &[f64]) -> (f64, f64) {
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if min == max {
+        (min - 1.0, max + 1.0)
+    } else {
+        (min, max)
+    }
+}
+
+/// Punkty kwadratowej siatki `RESOLUTION x RESOLUTION` na dziedzinie `[min, max]^2`.
+pub(crate) fn grid_points(min: f64, max: f64) -> Vec<Vec<f64>> {
+    let axis = linspace(min, max, RESOLUTION);
+    axis.iter()
+        .flat_map(|&x1| axis.iter().map(move |&x2| vec![x1, x2]))
+        .collect()
+}
+
+/// Rysuje obok siebie powierzchnię funkcji docelowej i powierzchnię aproksymowaną przez sieć.
+///
+/// # Argumenty
+///
+/// * `mlp` - Wytrenowana sieć o dwóch wejściach.
+/// * `function` - Aproksymowana funkcja.
+/// * `min`, `max` - Dziedzina każdej ze współrzędnych.
+/// * `path` - Ścieżka pliku SVG.
+pub(crate) fn plot_surfaces(
+    mlp: &MLP,
+    function: TargetFunction,
+    min: f64,
+    max: f64,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let axis = linspace(min, max, RESOLUTION);
+    let grid = grid_points(min, max);
+    let target: Vec<f64> = grid.iter().map(|x| function.evaluate(x, (min, max))).collect();
+    let predicted = mlp.predict(&grid);
+    let (z_min, z_max) = value_range(&[target.as_slice(), predicted.as_slice()].concat());
+
+    let root = SVGBackend::new(path, (1200, 600)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let panels = root.split_evenly((1, 2));
+
+    for (panel, (title, values)) in panels
+        .iter()
+        .zip([("Funkcja docelowa", &target), ("Predykcja sieci", &predicted)])
+    {
+        let mut chart = ChartBuilder::on(panel)
+            .caption(title, ("sans-serif", 24))
+            .build_cartesian_3d(min..max, z_min..z_max, min..max)?;
+        chart.with_projection(|mut p| {
+            p.pitch = 0.6;
+            p.yaw = 0.8;
+            p.scale = 0.8;
+            p.into_matrix()
+        });
+        chart.configure_axes().light_grid_style(BLACK.mix(0.15)).draw()?;
+
+        // Wartość w punkcie siatki (x1, x2) odczytywana z obliczonego wektora
+        let value_at = |x1: f64, x2: f64| {
+            let i = ((x1 - min) / (max - min) * (RESOLUTION - 1) as f64).round() as usize;
+            let j = ((x2 - min) / (max - min) * (RESOLUTION - 1) as f64).round() as usize;
+            values[i * RESOLUTION + j]
+        };
+        let style = |&v: &f64| ViridisRGB::get_color((v - z_min) / (z_max - z_min)).mix(0.8).filled();
+        chart.draw_series(
+            SurfaceSeries::xoz(axis.iter().copied(), axis.iter().copied(), value_at).style_func(&style),
+        )?;
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+/// Skala barw map cieplnych.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ColorScale {
+    /// Paleta viridis na przedziale `[min, max]`.
+    Sequential(f64, f64),
+    /// Niebieski dla `-limit`, biały dla 0 i czerwony dla `limit`.
+    Diverging(f64),
+}
+
+impl ColorScale {
+    /// Kolor wartości; wartości spoza skali przyjmują kolor jej końca.
+    fn color(&self, v: f64) -> RGBColor {
+        match *self {
+            ColorScale::Sequential(min, max) => ViridisRGB::get_color(((v - min) / (max - min)).clamp(0.0, 1.0)),
+            ColorScale::Diverging(limit) => {
+                let t = (v / limit).clamp(-1.0, 1.0);
+                let end: (f64, f64, f64) = if t < 0.0 { (59.0, 76.0, 192.0) } else { (180.0, 4.0, 38.0) };
+                let mix = |c: f64| (255.0 + (c - 255.0) * t.abs()).round() as u8;
+                RGBColor(mix(end.0), mix(end.1), mix(end.2))
+            }
+        }
+    }
+}
+
+/// Rysuje mapę cieplną wartości w punktach siatki z `grid_points`, opcjonalnie z naniesionymi
+/// punktami danych.
+///
+/// # Argumenty
+///
+/// * `values` - Wartości w kolejnych punktach siatki.
+/// * `points` - Punkty rysowane na mapie (np. dane uczące).
+/// * `min`, `max` - Dziedzina każdej ze współrzędnych.
+/// * `caption` - Tytuł wykresu.
+/// * `path` - Ścieżka pliku SVG.
+fn draw_heatmap(
+    values: &[f64],
+    points: &[Vec<f64>],
+    min: f64,
+    max: f64,
+    caption: &str,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let max_value = values.iter().cloned().fold(f64::MIN_POSITIVE, f64::max);
+    let root = SVGBackend::new(path, (700, 600)).into_drawing_area();
+    root.fill(&WHITE)?;
+    draw_heatmap_panel(&root, values, points, min, max, caption, 24, ColorScale::Sequential(0.0, max_value))?;
+    root.present()?;
+    Ok(())
+}
+
+/// Rysuje mapę cieplną w podanym obszarze rysunku.
+///
+/// # Argumenty
+///
+/// * `area` - Obszar rysunku (cały plik lub panel siatki).
+/// * `values` - Wartości w kolejnych punktach siatki.
+/// * `points` - Punkty rysowane na mapie.
+/// * `min`, `max` - Dziedzina każdej ze współrzędnych.
+/// * `caption` - Tytuł wykresu.
+/// * `font_size` - Rozmiar czcionki tytułu; od niego zależą też marginesy osi.
+/// * `scale` - Skala barw.
+#[allow(clippy::too_many_arguments)]
+fn draw_heatmap_panel(
+    area: &DrawingArea<SVGBackend, Shift>,
+    values: &[f64],
+    points: &[Vec<f64>],
+    min: f64,
+    max: f64,
+    caption: &str,
+    font_size: u32,
+    scale: ColorScale,
+) -> Result<(), Box<dyn Error>> {
+    let step = (max - min) / (RESOLUTION - 1) as f64;
+    let grid = grid_points(min, max);
+
+    let mut chart = ChartBuilder::on(area)
+        .caption(caption, ("sans-serif", font_size))
+        .margin(font_size / 2)
+        .x_label_area_size(font_size * 5 / 3)
+        .y_label_area_size(font_size * 2)
+        .build_cartesian_2d(min - step / 2.0..max + step / 2.0, min - step / 2.0..max + step / 2.0)?;
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_desc("x1")
+        .y_desc("x2")
+        .draw()?;
+
+    chart.draw_series(grid.iter().zip(values.iter()).map(|(x, v)| {
+        Rectangle::new(
+            [(x[0] - step / 2.0, x[1] - step / 2.0), (x[0] + step / 2.0, x[1] + step / 2.0)],
+            scale.color(*v).filled(),
+        )
+    }))?;
+    chart.draw_series(points.iter().map(|x| Circle::new((x[0], x[1]), 2, WHITE.mix(0.7).filled())))?;
+    Ok(())
+}
+
+/// Rysuje siatkę map cieplnych ze wspólną skalą barw, np. po jednej mapie na neuron ukryty.
+///
+/// # Argumenty
+///
+/// * `maps` - Tytuł i wartości w punktach siatki `grid_points` każdej mapy.
+/// * `min`, `max` - Dziedzina każdej ze współrzędnych.
+/// * `scale` - Skala barw wspólna dla wszystkich map.
+/// * `caption` - Tytuł całego rysunku.
+/// * `path` - Ścieżka pliku SVG.
+pub(crate) fn plot_map_grid(
+    maps: &[(String, Vec<f64>)],
+    min: f64,
+    max: f64,
+    scale: ColorScale,
+    caption: &str,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    const PANEL: u32 = 220;
+    let cols = (maps.len() as f64).sqrt().ceil().max(1.0) as usize;
+    let rows = maps.len().div_ceil(cols).max(1);
+
+    let root = SVGBackend::new(path, (cols as u32 * PANEL, rows as u32 * PANEL + 40)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let body = root.titled(caption, ("sans-serif", 24))?;
+    for (panel, (title, values)) in body.split_evenly((rows, cols)).iter().zip(maps.iter()) {
+        draw_heatmap_panel(panel, values, &[], min, max, title, 14, scale)?;
+    }
+    root.present()?;
+    Ok(())
+}
+
+/// Rysuje mapę cieplną błędu bezwzględnego `|predykcja - wartość docelowa|`.
+///
+/// # Argumenty
+///
+/// * `mlp` - Wytrenowana sieć o dwóch wejściach.
+/// * `function` - Aproksymowana funkcja.
+/// * `min`, `max` - Dziedzina każdej ze współrzędnych.
+/// * `path` - Ścieżka pliku SVG.
+pub(crate) fn plot_error_heatmap(
+    mlp: &MLP,
+    function: TargetFunction,
+    min: f64,
+    max: f64,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let grid = grid_points(min, max);
+    let errors: Vec<f64> = grid
+        .iter()
+        .zip(mlp.predict(&grid))
+        .map(|(x, y)| (y - function.evaluate(x, (min, max))).abs())
+        .collect();
+    let max_error = errors.iter().cloned().fold(f64::MIN_POSITIVE, f64::max);
+    draw_heatmap(&errors, &[], min, max, &format!("Błąd bezwzględny (maks. {:.4})", max_error), path)
+}
+
+/// Rysuje mapę niepewności zespołu: odchylenie standardowe predykcji członków zespołu
+/// w punktach siatki, z naniesionymi danymi uczącymi.
+///
+/// # Argumenty
+///
+/// * `ensemble` - Wytrenowany zespół sieci o dwóch wejściach.
+/// * `data` - Dane uczące zespołu.
+/// * `min`, `max` - Dziedzina każdej ze współrzędnych.
+/// * `path` - Ścieżka pliku SVG.
+pub(crate) fn plot_uncertainty_map(
+    ensemble: &Ensemble,
+    data: &[Vec<f64>],
+    min: f64,
+    max: f64,
+    path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let (_, std) = ensemble.predict_with_std(&grid_points(min, max));
+    let max_std = std.iter().cloned().fold(f64::MIN_POSITIVE, f64::max);
+    draw_heatmap(&std, data, min, max, &format!("Odchylenie standardowe zespołu (maks. {:.4})", max_std), path)
+}
+
+/// Rysuje krzywe straty uczenia i walidacji w skali logarytmicznej.
+///
+/// # Argumenty
+///
+/// * `history` - Historia zwrócona przez `MLP::fit`.
+/// * `path` - Ścieżka pliku SVG.
+pub(crate) fn plot_loss_curve(history: &TrainingHistory, path: &str) -> Result<(), Box<dyn Error>> {
+    let Some(&last_step) = history.steps.last() else {
+        return Ok(());
+    };
+    let all_losses = [history.train_loss.as_slice(), history.validation_loss.as_slice()].concat();
+    let (loss_min, loss_max) = value_range(&all_losses);
+    // Skala logarytmiczna wymaga dodatnich końców i niepustego przedziału, także przy zerowej stracie
+    let low = loss_min.max(1e-12);
+    let high = loss_max.max(low * 10.0);
+
+    let root = SVGBackend::new(path, (800, 500)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Krzywa uczenia", ("sans-serif", 24))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(70)
+        .build_cartesian_2d(0..last_step, (low..high).log_scale())?;
+    chart
+        .configure_mesh()
+        .x_desc("Krok uczenia")
+        .y_desc("Strata")
+        .draw()?;
+
+    chart
+        .draw_series(LineSeries::new(
+            history.steps.iter().copied().zip(history.train_loss.iter().copied()),
+            &BLUE,
+        ))?
+        .label("Uczenie")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+    if !history.validation_loss.is_empty() {
+        chart
+            .draw_series(LineSeries::new(
+                history.steps.iter().copied().zip(history.validation_loss.iter().copied()),
+                &RED,
+            ))?
+            .label("Walidacja")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_data, generate_training_data, MODEL_RNG_SEED, RNG_SEED};
+    use std::f64::consts::PI;
+    use std::fs;
+
+    #[test]
+    fn test_plots_are_written() {
+        let data = generate_data(50, 0.0, PI, RNG_SEED);
+        let mut mlp = MLP::new(2, 4, 1, 300, 0.1, MODEL_RNG_SEED);
+        mlp.config.eval_every = 50;
+        let history = mlp.fit(&data, &generate_training_data(&data));
+
+        let dir = std::env::temp_dir();
+        let paths: Vec<String> = ["surfaces", "error", "loss"]
+            .iter()
+            .map(|name| dir.join(format!("mlp_{}_{}.svg", name, std::process::id())).to_string_lossy().into_owned())
+            .collect();
+        plot_surfaces(&mlp, TargetFunction::CosProduct, 0.0, PI, &paths[0]).unwrap();
+        plot_error_heatmap(&mlp, TargetFunction::CosProduct, 0.0, PI, &paths[1]).unwrap();
+        plot_loss_curve(&history, &paths[2]).unwrap();
+        // Idealne dopasowanie: wszystkie straty poniżej dolnego ograniczenia osi
+        let perfect = TrainingHistory { steps: vec![0, 10], train_loss: vec![0.0, 0.0], ..Default::default() };
+        plot_loss_curve(&perfect, &paths[2]).unwrap();
+
+        for path in paths {
+            let svg = fs::read_to_string(&path).unwrap();
+            assert!(svg.starts_with("<svg"));
+            fs::remove_file(&path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::matrix::{dot, solve_positive_definite, Matrix};
+use crate::model_file::ModelError;
+use crate::Regressor;
+
+/// Maksymalna liczba iteracji algorytmu k-średnich.
+const KMEANS_ITERATIONS: usize = 100;
+/// Kolejne względne regularyzacje grzbietowe układu równań normalnych, chroniące przed złym
+/// uwarunkowaniem; większa jest próbowana dopiero, gdy przy mniejszej rozkład się nie powiedzie.
+const RIDGES: [f64; 5] = [1e-8, 1e-6, 1e-4, 1e-2, 1.0];
+
+/// Sposób wyboru centrów funkcji bazowych.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Centres {
+    KMeans, // Centra skupień algorytmu k-średnich
+    Random, // Losowo wybrane próbki uczące
+}
+
+/// Heurystyka szerokości funkcji gaussowskich.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Width {
+    /// Wspólna szerokość `d_max / sqrt(2M)`, gdzie `d_max` to największa odległość między centrami.
+    MaxDistance,
+    /// Szerokość każdego centrum równa średniej odległości od `p` najbliższych innych centrów.
+    NearestCentres(usize),
+}
+
+/// Sieć o radialnych funkcjach bazowych: warstwa gaussowskich funkcji bazowych
+/// `exp(-|x - c_j|^2 / (2 sigma_j^2))` i liniowe wyjście z biasem.
+pub(crate) struct RBF {
+    M: usize,                             // Liczba funkcji bazowych
+    centres: Matrix,                      // Centra, jeden wiersz na funkcję bazową
+    widths: Vec<f64>,                     // Szerokość sigma każdej funkcji bazowej
+    weights: Vec<f64>,                    // Wagi wyjścia, ostatnia to bias
+    pub(crate) centre_selection: Centres, // Sposób wyboru centrów
+    pub(crate) width: Width,              // Heurystyka szerokości
+    seed: u64,                            // Ziarno wyboru centrów
+}
+
+/// Kwadrat odległości euklidesowej.
+fn squared_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(a, b)| (a - b).powi(2)).sum()
+}
+
+impl RBF {
+    /// Tworzy nienauczoną sieć RBF; centra i wagi wyznacza `RBF::fit`.
+    ///
+    /// Domyślnie centra są wybierane algorytmem k-średnich, a szerokości heurystyką
+    /// dwóch najbliższych centrów.
+    ///
+    /// # Argumenty
+    ///
+    /// * `n_inputs` - Liczba wejść sieci
+    /// * `M` - Liczba funkcji bazowych
+    /// * `seed` - Ziarno generatora używanego przy wyborze centrów
+    pub(crate) fn new(n_inputs: usize, M: usize, seed: u64) -> RBF {
+        RBF {
+            M,
+            centres: Matrix::zeros(M, n_inputs),
+            widths: vec![1.0; M],
+            weights: vec![0.0; M + 1],
+            centre_selection: Centres::KMeans,
+            width: Width::NearestCentres(2),
+            seed,
+        }
+    }
+
+    /// Dopasowuje sieć do danych: wybiera centra, wyznacza szerokości i rozwiązuje
+    /// zadanie najmniejszych kwadratów dla wag wyjścia.
+    ///
+    /// # Argumenty
+    ///
+    /// * `data` - Wycinek wektorów reprezentujących dane wejściowe.
+    /// * `targets` - Wartości docelowe.
+    ///
+    /// # Zwraca
+    ///
+    /// Błąd `ModelError::Fit`, gdy układ równań normalnych nie daje się rozwiązać przy żadnej
+    /// regularyzacji z `RIDGES`, co oznacza wartości nieskończone lub NaN w danych.
+    pub(crate) fn fit(&mut self, data: &[Vec<f64>], targets: &[f64]) -> Result<(), ModelError> {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let M = self.M.min(data.len());
+
+        // Początkowe centra: losowe, różne próbki uczące
+        let chosen: Vec<Vec<f64>> = data.choose_multiple(&mut rng, M).cloned().collect();
+        self.centres = Matrix::from_rows(&chosen);
+        if self.centre_selection == Centres::KMeans {
+            self.kmeans(data);
+        }
+        self.widths = self.compute_widths();
+
+        // Równania normalne (PhiᵀPhi + lambda I) w = Phiᵀ t
+        let cols = M + 1;
+        let mut gram = Matrix::zeros(cols, cols);
+        let mut rhs = vec![0.0; cols];
+        for (x, t) in data.iter().zip(targets.iter()) {
+            let phi = self.features(x);
+            for i in 0..cols {
+                rhs[i] += phi[i] * t;
+                for (g, p) in gram.row_mut(i)[..=i].iter_mut().zip(phi.iter()) {
+                    *g += phi[i] * p;
+                }
+            }
+        }
+        for i in 0..cols {
+            for j in 0..i {
+                let value = gram.row(i)[j];
+                gram.row_mut(j)[i] = value;
+            }
+        }
+        self.weights = solve_ridge(&gram, &rhs).ok_or_else(|| {
+            ModelError::Fit("układ równań sieci RBF nie jest dodatnio określony (NaN lub nieskończoność w danych?)".into())
+        })?;
+        Ok(())
+    }
+
+    /// Algorytm k-średnich (Lloyda) zaczynający od bieżących centrów; puste skupienia
+    /// zachowują poprzednie centrum.
+    fn kmeans(&mut self, data: &[Vec<f64>]) {
+        let (k, dim) = (self.centres.rows(), self.centres.cols());
+        let mut assignment = vec![usize::MAX; data.len()];
+        for _ in 0..KMEANS_ITERATIONS {
+            let mut changed = false;
+            for (x, a) in data.iter().zip(assignment.iter_mut()) {
+                let nearest = (0..k)
+                    .min_by(|&i, &j| {
+                        squared_distance(x, self.centres.row(i)).total_cmp(&squared_distance(x, self.centres.row(j)))
+                    })
+                    .unwrap_or_default();
+                changed |= *a != nearest;
+                *a = nearest;
+            }
+            if !changed {
+                break;
+            }
+
+            let mut sums = Matrix::zeros(k, dim);
+            let mut counts = vec![0usize; k];
+            for (x, &a) in data.iter().zip(assignment.iter()) {
+                counts[a] += 1;
+                for (s, v) in sums.row_mut(a).iter_mut().zip(x.iter()) {
+                    *s += v;
+                }
+            }
+            for (j, &count) in counts.iter().enumerate().filter(|(_, &c)| c > 0) {
+                for (c, s) in self.centres.row_mut(j).iter_mut().zip(sums.row(j).iter()) {
+                    *c = s / count as f64;
+                }
+            }
+        }
+    }
+
+    /// Szerokości funkcji bazowych według wybranej heurystyki.
+    fn compute_widths(&self) -> Vec<f64> {
+        let k = self.centres.rows();
+        let distances: Vec<Vec<f64>> = (0..k)
+            .map(|i| {
+                (0..k)
+                    .filter(|&j| j != i)
+                    .map(|j| squared_distance(self.centres.row(i), self.centres.row(j)).sqrt())
+                    .collect()
+            })
+            .collect();
+
+        let widths: Vec<f64> = match self.width {
+            Width::MaxDistance => {
+                let d_max = distances.iter().flatten().fold(0.0, |a: f64, &b| a.max(b));
+                vec![d_max / (2.0 * k as f64).sqrt(); k]
+            }
+            Width::NearestCentres(p) => distances
+                .into_iter()
+                .map(|mut d| {
+                    d.sort_by(f64::total_cmp);
+                    let p = p.clamp(1, d.len().max(1));
+                    d.iter().take(p).sum::<f64>() / p as f64
+                })
+                .collect(),
+        };
+        // Pokrywające się centra dawałyby zerową szerokość
+        widths.into_iter().map(|w| if w > 0.0 { w } else { 1.0 }).collect()
+    }
+
+    /// Wartości funkcji bazowych w punkcie `x` oraz stała 1 dla biasu.
+    fn features(&self, x: &[f64]) -> Vec<f64> {
+        self.centres
+            .iter_rows()
+            .zip(self.widths.iter())
+            .map(|(c, w)| (-squared_distance(x, c) / (2.0 * w * w)).exp())
+            .chain(std::iter::once(1.0))
+            .collect()
+    }
+
+    /// Przewiduje wynik dla danego zestawu punktów danych.
+    ///
+    /// # Argumenty
+    ///
+    /// * `data` - Wycinek wektorów reprezentujących punkty danych wejściowych.
+    ///
+    /// # Zwraca
+    ///
+    /// Wektor przewidywanych wartości dla każdego punktu danych.
+    pub(crate) fn predict(&self, data: &[Vec<f64>]) -> Vec<f64> {
+        data.iter().map(|x| dot(&self.features(x), &self.weights)).collect()
+    }
+}
+
+/// Rozwiązuje układ `(gram + lambda I) w = rhs`, zwiększając `lambda` zgodnie z `RIDGES`
+/// (względem średniej przekątnej `gram`), aż macierz stanie się dodatnio określona.
+///
+/// # Zwraca
+///
+/// Rozwiązanie albo `None`, gdy rozkład nie powiedzie się przy żadnej regularyzacji.
+fn solve_ridge(gram: &Matrix, rhs: &[f64]) -> Option<Vec<f64>> {
+    let cols = gram.rows();
+    let scale = (0..cols).map(|i| gram.row(i)[i]).sum::<f64>() / cols as f64;
+    // Bez danych przekątna jest zerowa i regularyzacja względna nic by nie zmieniła
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    RIDGES.iter().find_map(|&ridge| {
+        let mut regularized = gram.clone();
+        for i in 0..cols {
+            regularized.row_mut(i)[i] += ridge * scale;
+        }
+        solve_positive_definite(&regularized, rhs)
+    })
+}
+
+impl Regressor for RBF {
+    fn fit(&mut self, data: &[Vec<f64>], targets: &[f64]) -> Result<(), ModelError> {
+        RBF::fit(self, data, targets)
+    }
+
+    fn predict(&self, data: &[Vec<f64>]) -> Vec<f64> {
+        RBF::predict(self, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loss::Loss;
+    use crate::{generate_data, generate_training_data, RNG_SEED, TEST_RNG_SEED};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_rbf_interpolates_target_function() {
+        let data = generate_data(500, 0.0, PI, RNG_SEED);
+        let targets = generate_training_data(&data);
+        let test_data = generate_data(500, 0.0, PI, TEST_RNG_SEED);
+        let test_targets = generate_training_data(&test_data);
+
+        for centres in [Centres::KMeans, Centres::Random] {
+            for width in [Width::MaxDistance, Width::NearestCentres(2)] {
+                let mut rbf = RBF::new(2, 40, 1);
+                rbf.centre_selection = centres;
+                rbf.width = width;
+                rbf.fit(&data, &targets).unwrap();
+                let mse = Loss::MSE.mean(&rbf.predict(&test_data), &test_targets);
+                // Centra k-średnich pokrywają dziedzinę równomierniej niż losowe próbki
+                let limit = if centres == Centres::KMeans { 0.01 } else { 0.1 };
+                assert!(mse < limit, "{:?} {:?}: {}", centres, width, mse);
+            }
+        }
+
+        // Tyle centrów co próbek: dokładna interpolacja w punktach uczących
+        let few = &data[..20];
+        let mut rbf = RBF::new(2, 20, 1);
+        rbf.centre_selection = Centres::Random;
+        rbf.fit(few, &targets[..20]).unwrap();
+        let mse = Loss::MSE.mean(&rbf.predict(few), &targets[..20]);
+        assert!(mse < 1e-6, "{}", mse);
+    }
+
+    #[test]
+    fn test_ridge_is_increased_until_solvable() {
+        // Wartości własne ok. 2 i -5e-6: rozkład udaje się dopiero przy regularyzacji 1e-4
+        let gram = Matrix::from_rows(&[vec![1.0, 1.0], vec![1.0, 1.0 - 1e-5]]);
+        assert!(solve_positive_definite(&gram, &[1.0, 1.0]).is_none());
+        let w = solve_ridge(&gram, &[1.0, 1.0]).unwrap();
+        assert!(w.iter().all(|v| v.is_finite()));
+
+        let nan = Matrix::from_rows(&[vec![f64::NAN]]);
+        assert!(solve_ridge(&nan, &[1.0]).is_none());
+
+        let mut data = generate_data(20, 0.0, PI, RNG_SEED);
+        let targets = generate_training_data(&data);
+        data[3][0] = f64::NAN;
+        assert!(matches!(RBF::new(2, 5, 1).fit(&data, &targets), Err(ModelError::Fit(_))));
+    }
+}
@@ -0,0 +1,120 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::float::Float;
+
+/// Sposób skalowania wartości wejściowych lub docelowych.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, ValueEnum)]
+pub(crate) enum Scaling {
+    /// Wartości są przekazywane bez zmian.
+    None,
+    /// Standaryzacja do średniej 0 i odchylenia standardowego 1.
+    Standard,
+    /// Przeskalowanie do przedziału `[0, 1]`.
+    MinMax,
+}
+
+/// Skaler dopasowany do zbioru uczącego: `x' = (x - offset) / scale` dla każdej kolumny.
+///
+/// Parametry są przechowywane w `f64` niezależnie od typu skalowanych wartości.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Scaler {
+    pub(crate) scaling: Scaling,
+    pub(crate) offset: Vec<f64>,
+    pub(crate) scale: Vec<f64>,
+}
+
+impl Scaler {
+    /// Wyznacza parametry skalowania każdej kolumny na podstawie podanych wierszy.
+    ///
+    /// Kolumny o stałej wartości dostają skalę 1, aby uniknąć dzielenia przez zero.
+    ///
+    /// # Argumenty
+    ///
+    /// * `scaling` - Sposób skalowania.
+    /// * `rows` - Wiersze zbioru uczącego.
+    ///
+    /// # Zwraca
+    ///
+    /// Skaler albo `None` dla `Scaling::None` i pustego zbioru.
+    pub(crate) fn fit<F: Float>(scaling: Scaling, rows: &[Vec<F>]) -> Option<Scaler> {
+        if scaling == Scaling::None || rows.is_empty() {
+            return None;
+        }
+        let n = rows.len() as f64;
+        let columns = rows[0].len();
+        let mut offset = Vec::with_capacity(columns);
+        let mut scale = Vec::with_capacity(columns);
+        for column in 0..columns {
+            let values = rows.iter().map(|row| row[column].as_f64());
+            let (o, s) = match scaling {
+                Scaling::Standard => {
+                    let mean = values.clone().sum::<f64>() / n;
+                    let var = values.map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+                    (mean, var.sqrt())
+                }
+                Scaling::MinMax | Scaling::None => {
+                    let min = values.clone().fold(f64::INFINITY, f64::min);
+                    let max = values.fold(f64::NEG_INFINITY, f64::max);
+                    (min, max - min)
+                }
+            };
+            offset.push(o);
+            scale.push(if s > 0.0 { s } else { 1.0 });
+        }
+        Some(Scaler { scaling, offset, scale })
+    }
+
+    /// Skaluje jeden wiersz.
+    pub(crate) fn transform<F: Float>(&self, row: &[F]) -> Vec<F> {
+        row.iter()
+            .zip(self.offset.iter().zip(self.scale.iter()))
+            .map(|(x, (o, s))| F::of((x.as_f64() - o) / s))
+            .collect()
+    }
+
+    /// Skaluje wszystkie wiersze.
+    pub(crate) fn transform_rows<F: Float>(&self, rows: &[Vec<F>]) -> Vec<Vec<F>> {
+        rows.iter().map(|row| self.transform(row)).collect()
+    }
+
+    /// Odwraca skalowanie jednego wiersza.
+    pub(crate) fn inverse_transform<F: Float>(&self, row: &[F]) -> Vec<F> {
+        row.iter()
+            .zip(self.offset.iter().zip(self.scale.iter()))
+            .map(|(x, (o, s))| F::of(x.as_f64() * s + o))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalers_round_trip() {
+        let rows = vec![vec![1.0, 10.0, 5.0], vec![3.0, 20.0, 5.0], vec![5.0, 60.0, 5.0]];
+
+        let standard = Scaler::fit(Scaling::Standard, &rows).unwrap();
+        let scaled = standard.transform_rows(&rows);
+        for column in 0..2 {
+            let mean = scaled.iter().map(|r| r[column]).sum::<f64>() / 3.0;
+            let var = scaled.iter().map(|r| (r[column] - mean).powi(2)).sum::<f64>() / 3.0;
+            assert!(mean.abs() < 1e-12 && (var - 1.0).abs() < 1e-12);
+        }
+        // Stała kolumna jest tylko przesuwana
+        assert_eq!(scaled[0][2], 0.0);
+
+        let min_max = Scaler::fit(Scaling::MinMax, &rows).unwrap();
+        assert_eq!(min_max.transform(&rows[2]), vec![1.0, 1.0, 0.0]);
+        assert_eq!(min_max.transform(&rows[0]), vec![0.0, 0.0, 0.0]);
+
+        for scaler in [standard, min_max] {
+            for row in rows.iter() {
+                let back = scaler.inverse_transform(&scaler.transform(row));
+                assert!(back.iter().zip(row.iter()).all(|(a, b)| (a - b).abs() < 1e-12));
+            }
+        }
+        assert!(Scaler::fit(Scaling::None, &rows).is_none());
+    }
+}
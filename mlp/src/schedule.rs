@@ -0,0 +1,130 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+/// Harmonogram zmian współczynnika uczenia w trakcie uczenia.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Schedule {
+    /// Stały współczynnik uczenia.
+    Constant,
+    /// Mnożenie przez `gamma` co `step_size` kroków.
+    StepDecay { step_size: usize, gamma: f64 },
+    /// Ciągły spadek `lr * gamma^(krok / decay_steps)`.
+    ExponentialDecay { decay_steps: usize, gamma: f64 },
+    /// Wyżarzanie kosinusowe od `lr` do `min_lr` z restartami; każdy kolejny okres
+    /// jest `period_mult` razy dłuższy od poprzedniego.
+    CosineWarmRestarts { period: usize, period_mult: usize, min_lr: f64 },
+    /// Mnożenie przez `factor`, gdy strata nie poprawiła się przez więcej niż `patience` ocen.
+    ReduceOnPlateau { factor: f64, patience: usize, min_lr: f64 },
+}
+
+/// Stan harmonogramu w trakcie jednego uczenia.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct Scheduler {
+    schedule: Schedule,
+    warmup_steps: usize,    // Liczba kroków liniowego rozgrzewania
+    plateau_scale: f64,     // Aktualny mnożnik dla `ReduceOnPlateau`
+    best_loss: Option<f64>, // Najmniejsza zaobserwowana strata
+    bad_evaluations: usize, // Liczba ocen bez poprawy straty
+}
+
+impl Scheduler {
+    /// Tworzy harmonogram z opcjonalnym liniowym rozgrzewaniem przez `warmup_steps` kroków.
+    pub(crate) fn new(schedule: Schedule, warmup_steps: usize) -> Self {
+        Scheduler {
+            schedule,
+            warmup_steps,
+            plateau_scale: 1.0,
+            best_loss: None,
+            bad_evaluations: 0,
+        }
+    }
+
+    /// Współczynnik uczenia dla kroku `step` (liczonego od zera).
+    ///
+    /// # Argumenty
+    ///
+    /// * `base` - Początkowy współczynnik uczenia.
+    /// * `step` - Numer kroku uczenia.
+    pub(crate) fn learning_rate(&self, base: f64, step: usize) -> f64 {
+        // Liniowe rozgrzewanie, po którym harmonogram liczy kroki od zera
+        if step < self.warmup_steps {
+            return base * (step + 1) as f64 / self.warmup_steps as f64;
+        }
+        let step = step - self.warmup_steps;
+
+        match self.schedule {
+            Schedule::Constant => base,
+            Schedule::StepDecay { step_size, gamma } => base * gamma.powi((step / step_size.max(1)) as i32),
+            Schedule::ExponentialDecay { decay_steps, gamma } => {
+                base * gamma.powf(step as f64 / decay_steps.max(1) as f64)
+            }
+            Schedule::CosineWarmRestarts { period, period_mult, min_lr } => {
+                // Wyznaczenie pozycji w bieżącym okresie
+                let mut t = step;
+                let mut length = period.max(1);
+                while t >= length {
+                    t -= length;
+                    length *= period_mult.max(1);
+                }
+                min_lr + 0.5 * (base - min_lr) * (1.0 + f64::cos(PI * t as f64 / length as f64))
+            }
+            Schedule::ReduceOnPlateau { min_lr, .. } => (base * self.plateau_scale).max(min_lr),
+        }
+    }
+
+    /// Przekazuje harmonogramowi stratę z kolejnej oceny modelu.
+    pub(crate) fn observe(&mut self, loss: f64) {
+        if let Schedule::ReduceOnPlateau { factor, patience, .. } = self.schedule {
+            if self.best_loss.is_none_or(|best| loss < best) {
+                self.best_loss = Some(loss);
+                self.bad_evaluations = 0;
+            } else {
+                self.bad_evaluations += 1;
+                if self.bad_evaluations > patience {
+                    self.plateau_scale *= factor;
+                    self.bad_evaluations = 0;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedules() {
+        let step = Scheduler::new(Schedule::StepDecay { step_size: 10, gamma: 0.5 }, 0);
+        assert_eq!(step.learning_rate(1.0, 9), 1.0);
+        assert_eq!(step.learning_rate(1.0, 10), 0.5);
+        assert_eq!(step.learning_rate(1.0, 25), 0.25);
+
+        let exponential = Scheduler::new(Schedule::ExponentialDecay { decay_steps: 10, gamma: 0.1 }, 0);
+        assert!((exponential.learning_rate(1.0, 20) - 0.01).abs() < 1e-12);
+
+        let cosine = Scheduler::new(Schedule::CosineWarmRestarts { period: 10, period_mult: 2, min_lr: 0.0 }, 0);
+        assert_eq!(cosine.learning_rate(1.0, 0), 1.0);
+        assert!((cosine.learning_rate(1.0, 5) - 0.5).abs() < 1e-12);
+        // Restart po 10 krokach, następny okres trwa 20 kroków
+        assert_eq!(cosine.learning_rate(1.0, 10), 1.0);
+        assert!((cosine.learning_rate(1.0, 20) - 0.5).abs() < 1e-12);
+        assert_eq!(cosine.learning_rate(1.0, 30), 1.0);
+
+        let warmup = Scheduler::new(Schedule::Constant, 4);
+        assert_eq!(warmup.learning_rate(1.0, 0), 0.25);
+        assert_eq!(warmup.learning_rate(1.0, 3), 1.0);
+        assert_eq!(warmup.learning_rate(1.0, 100), 1.0);
+
+        let mut plateau = Scheduler::new(Schedule::ReduceOnPlateau { factor: 0.1, patience: 1, min_lr: 0.005 }, 0);
+        for loss in [1.0, 0.5, 0.6, 0.6] {
+            plateau.observe(loss);
+        }
+        assert!((plateau.learning_rate(1.0, 0) - 0.1).abs() < 1e-12);
+        for _ in 0..4 {
+            plateau.observe(0.6);
+        }
+        assert_eq!(plateau.learning_rate(1.0, 0), 0.005);
+    }
+}
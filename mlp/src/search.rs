@@ -0,0 +1,195 @@
+use csv::Writer;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+use crate::rbf::Centres;
+
+/// Rodzaj porównywanego modelu.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Model {
+    Mlp,          // Perceptron wielowarstwowy uczony propagacją wsteczną
+    Rbf(Centres), // Sieć RBF z wybranym sposobem doboru centrów
+}
+
+impl Model {
+    /// Krótka nazwa modelu używana w wynikach.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Model::Mlp => "mlp",
+            Model::Rbf(Centres::KMeans) => "rbf-kmeans",
+            Model::Rbf(Centres::Random) => "rbf-random",
+        }
+    }
+}
+
+/// Pojedyncza konfiguracja hiperparametrów sieci.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Trial {
+    pub(crate) model: Model,          // Rodzaj modelu
+    pub(crate) N: usize,              // Liczba neuronów w warstwie ukrytej (funkcji bazowych dla RBF)
+    pub(crate) learning_steps: usize, // Liczba kroków uczenia (0 dla RBF)
+    pub(crate) learning_rate: f64,    // Współczynnik uczenia (0 dla RBF)
+}
+
+/// Wyniki powtórzeń jednej konfiguracji.
+pub(crate) struct TrialResult {
+    pub(crate) trial: Trial,
+    pub(crate) seeds: Vec<u64>,      // Ziarno każdego powtórzenia
+    pub(crate) test_mse: Vec<f64>,   // MSE każdego powtórzenia
+}
+
+impl TrialResult {
+    /// Średnie MSE z powtórzeń.
+    pub(crate) fn mean(&self) -> f64 {
+        self.test_mse.iter().sum::<f64>() / self.test_mse.len() as f64
+    }
+
+    /// Odchylenie standardowe MSE z powtórzeń (0 dla jednego powtórzenia).
+    pub(crate) fn std(&self) -> f64 {
+        let n = self.test_mse.len();
+        if n < 2 {
+            return 0.0;
+        }
+        let mean = self.mean();
+        let var = self.test_mse.iter().map(|m| (m - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+        var.sqrt()
+    }
+}
+
+/// Tworzy siatkę wszystkich kombinacji podanych wartości hiperparametrów sieci MLP.
+pub(crate) fn grid(N: &[usize], learning_steps: &[usize], learning_rate: &[f64]) -> Vec<Trial> {
+    let mut trials = Vec::new();
+    for &N in N {
+        for &learning_steps in learning_steps {
+            for &learning_rate in learning_rate {
+                trials.push(Trial { model: Model::Mlp, N, learning_steps, learning_rate });
+            }
+        }
+    }
+    trials
+}
+
+/// Konfiguracje sieci RBF dla podanych liczb funkcji bazowych, dla każdego sposobu doboru centrów.
+pub(crate) fn rbf_grid(N: &[usize]) -> Vec<Trial> {
+    [Centres::KMeans, Centres::Random]
+        .into_iter()
+        .flat_map(|centres| {
+            N.iter().map(move |&N| Trial {
+                model: Model::Rbf(centres),
+                N,
+                learning_steps: 0,
+                learning_rate: 0.0,
+            })
+        })
+        .collect()
+}
+
+/// Losuje `count` konfiguracji z podanych zakresów.
+///
+/// `N` i `learning_steps` są losowane równomiernie, a `learning_rate` log-równomiernie.
+pub(crate) fn random_trials(
+    count: usize,
+    N: (usize, usize),
+    learning_steps: (usize, usize),
+    learning_rate: (f64, f64),
+    seed: u64,
+) -> Vec<Trial> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let (log_min, log_max) = (learning_rate.0.ln(), learning_rate.1.ln());
+    (0..count)
+        .map(|_| Trial {
+            model: Model::Mlp,
+            N: rng.gen_range(N.0..=N.1),
+            learning_steps: rng.gen_range(learning_steps.0..=learning_steps.1),
+            learning_rate: rng.gen_range(log_min..=log_max).exp(),
+        })
+        .collect()
+}
+
+/// Równolegle ocenia każdą konfigurację `repeats` razy.
+///
+/// # Argumenty
+///
+/// * `trials` - Konfiguracje do oceny.
+/// * `repeats` - Liczba powtórzeń każdej konfiguracji.
+/// * `seed` - Ziarno bazowe; powtórzenie `r` konfiguracji `i` dostaje ziarno
+///   `seed + i * repeats + r`, więc wynik nie zależy od kolejności wykonania wątków.
+/// * `evaluate` - Uczy model dla konfiguracji i ziarna, zwracając MSE albo błąd.
+///
+/// # Zwraca
+///
+/// Wyniki wszystkich konfiguracji albo pierwszy napotkany błąd oceny.
+pub(crate) fn run_trials<F, E>(trials: &[Trial], repeats: usize, seed: u64, evaluate: F) -> Result<Vec<TrialResult>, E>
+where
+    F: Fn(&Trial, u64) -> Result<f64, E> + Sync,
+    E: Send,
+{
+    let jobs: Vec<(usize, u64)> = (0..trials.len())
+        .flat_map(|i| (0..repeats).map(move |r| (i, seed + (i * repeats + r) as u64)))
+        .collect();
+    let mse: Vec<f64> = jobs
+        .par_iter()
+        .map(|&(i, job_seed)| evaluate(&trials[i], job_seed))
+        .collect::<Result<_, E>>()?;
+
+    Ok(trials
+        .iter()
+        .enumerate()
+        .map(|(i, &trial)| TrialResult {
+            trial,
+            seeds: jobs[i * repeats..(i + 1) * repeats].iter().map(|j| j.1).collect(),
+            test_mse: mse[i * repeats..(i + 1) * repeats].to_vec(),
+        })
+        .collect())
+}
+
+/// Zapisuje wyniki przeszukiwania do pliku CSV z wierszem nagłówka.
+pub(crate) fn save_results(results: &[TrialResult], path: &str) -> Result<(), csv::Error> {
+    let mut wtr = Writer::from_path(path)?;
+    wtr.write_record(["model", "N", "learning_steps", "learning_rate", "repeats", "first_seed", "mean_mse", "std_mse"])?;
+    for result in results {
+        wtr.write_record([
+            result.trial.model.name().to_string(),
+            result.trial.N.to_string(),
+            result.trial.learning_steps.to_string(),
+            result.trial.learning_rate.to_string(),
+            result.test_mse.len().to_string(),
+            result.seeds.first().map_or(String::new(), |s| s.to_string()),
+            result.mean().to_string(),
+            result.std().to_string(),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_trials_is_deterministic() {
+        let trials = grid(&[2, 4], &[10], &[0.1, 0.01, 0.001]);
+        assert_eq!(trials.len(), 6);
+        assert_eq!(trials[1], Trial { model: Model::Mlp, N: 2, learning_steps: 10, learning_rate: 0.01 });
+
+        // Wynik zależy tylko od konfiguracji i ziarna
+        let evaluate = |trial: &Trial, seed: u64| Ok::<_, ()>(trial.N as f64 + (seed % 3) as f64);
+        let results = run_trials(&trials, 3, 100, evaluate).unwrap();
+        let again = run_trials(&trials, 3, 100, evaluate).unwrap();
+        for (a, b) in results.iter().zip(again.iter()) {
+            assert_eq!(a.seeds, b.seeds);
+            assert_eq!(a.test_mse, b.test_mse);
+        }
+
+        // Ziarna 100, 101, 102 dają reszty 1, 2, 0
+        assert_eq!(results[0].seeds, vec![100, 101, 102]);
+        assert_eq!(results[0].mean(), 3.0);
+        assert_eq!(results[0].std(), 1.0);
+
+        let random = random_trials(20, (2, 10), (100, 200), (0.001, 0.1), 7);
+        assert!(random.iter().all(|t| (2..=10).contains(&t.N) && (0.001..=0.1).contains(&t.learning_rate)));
+        assert_eq!(random, random_trials(20, (2, 10), (100, 200), (0.001, 0.1), 7));
+    }
+}
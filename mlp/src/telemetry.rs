@@ -0,0 +1,210 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Szerokość paska postępu w znakach.
+const BAR_WIDTH: usize = 30;
+/// Liczba odświeżeń paska postępu w trakcie całego uczenia.
+const BAR_UPDATES: usize = 200;
+
+/// Format pliku telemetrii uczenia.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+pub(crate) enum TelemetryFormat {
+    JsonLines, // Jeden obiekt JSON w wierszu
+    Csv,       // Plik CSV z nagłówkiem
+}
+
+/// Jeden wpis telemetrii, zapisywany co `config.telemetry_every` kroków.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TelemetryRecord {
+    pub(crate) step: usize,
+    pub(crate) train_loss: f64,                   // Strata na całym zbiorze uczącym
+    pub(crate) validation_loss: Option<f64>,      // Brak, gdy nie wydzielono zbioru walidacyjnego
+    pub(crate) gradient_norm: f64,                // Norma gradientu wsadu przed przycięciem
+    pub(crate) hidden_weight_norm: f64,           // Norma Frobeniusa wag warstwy ukrytej (z biasami)
+    pub(crate) output_weight_norm: f64,           // Norma Frobeniusa wag warstwy wyjściowej (z biasami)
+    pub(crate) learning_rate: f64,
+    pub(crate) elapsed_seconds: f64,              // Czas od rozpoczęcia (lub wznowienia) uczenia
+}
+
+/// Plik telemetrii w formacie JSON-lines lub CSV.
+pub(crate) struct TelemetryLog {
+    writer: BufWriter<File>,
+    format: TelemetryFormat,
+}
+
+impl TelemetryLog {
+    /// Otwiera plik telemetrii; przy `append` dopisuje do istniejącego pliku (wznowione uczenie)
+    /// i nie powtarza nagłówka CSV.
+    ///
+    /// # Argumenty
+    ///
+    /// * `path` - Ścieżka pliku.
+    /// * `format` - Format wpisów.
+    /// * `append` - Czy dopisywać do istniejącego pliku.
+    pub(crate) fn open(path: &str, format: TelemetryFormat, append: bool) -> io::Result<TelemetryLog> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)?;
+        let write_header = format == TelemetryFormat::Csv && file.metadata()?.len() == 0;
+        let mut log = TelemetryLog {
+            writer: BufWriter::new(file),
+            format,
+        };
+        if write_header {
+            writeln!(
+                log.writer,
+                "step,train_loss,validation_loss,gradient_norm,hidden_weight_norm,output_weight_norm,learning_rate,elapsed_seconds"
+            )?;
+        }
+        Ok(log)
+    }
+
+    /// Dopisuje wpis i opróżnia bufor, żeby plik można było śledzić w trakcie uczenia.
+    pub(crate) fn write(&mut self, record: &TelemetryRecord) -> io::Result<()> {
+        match self.format {
+            TelemetryFormat::JsonLines => {
+                serde_json::to_writer(&mut self.writer, record)?;
+                writeln!(self.writer)?;
+            }
+            TelemetryFormat::Csv => writeln!(
+                self.writer,
+                "{},{},{},{},{},{},{},{}",
+                record.step,
+                record.train_loss,
+                record.validation_loss.map_or(String::new(), |v| v.to_string()),
+                record.gradient_norm,
+                record.hidden_weight_norm,
+                record.output_weight_norm,
+                record.learning_rate,
+                record.elapsed_seconds
+            )?,
+        }
+        self.writer.flush()
+    }
+}
+
+/// Zwarty pasek postępu uczenia z szacowanym czasem do końca, rysowany na stderr.
+pub(crate) struct ProgressBar {
+    first_step: usize, // Krok, od którego liczony jest czas (większy od 0 po wznowieniu)
+    total: usize,      // Docelowa liczba kroków
+    start: Instant,
+}
+
+impl ProgressBar {
+    /// Tworzy pasek dla uczenia od kroku `first_step` do `total`.
+    pub(crate) fn new(first_step: usize, total: usize) -> ProgressBar {
+        ProgressBar {
+            first_step,
+            total,
+            start: Instant::now(),
+        }
+    }
+
+    /// Odświeża pasek co `total / BAR_UPDATES` kroków oraz w ostatnim kroku.
+    ///
+    /// # Argumenty
+    ///
+    /// * `step` - Liczba wykonanych kroków.
+    /// * `loss` - Ostatnio zmierzona strata uczenia, o ile już ją policzono.
+    pub(crate) fn update(&self, step: usize, loss: Option<f64>) {
+        if !step.is_multiple_of((self.total / BAR_UPDATES).max(1)) && step != self.total {
+            return;
+        }
+        eprint!("\r{}", self.render(step, loss, self.start.elapsed()));
+        if step == self.total {
+            eprintln!();
+        }
+        io::stderr().flush().ok();
+    }
+
+    /// Zakończenie paska przed końcem uczenia (wczesne zatrzymanie).
+    pub(crate) fn finish(&self, step: usize) {
+        if step != self.total {
+            eprintln!();
+        }
+    }
+
+    /// Tekst paska, np. `[#######.......]  50% 500/1000 strata 0.012345 ETA 00:07`.
+    fn render(&self, step: usize, loss: Option<f64>, elapsed: Duration) -> String {
+        let fraction = step as f64 / self.total.max(1) as f64;
+        let filled = ((fraction * BAR_WIDTH as f64) as usize).min(BAR_WIDTH);
+        let done = step.saturating_sub(self.first_step);
+        let remaining = self.total.saturating_sub(step);
+        let eta = if done > 0 {
+            format_duration(elapsed.as_secs_f64() / done as f64 * remaining as f64)
+        } else {
+            "--:--".to_string()
+        };
+        format!(
+            "[{}{}] {:>3.0}% {}/{} strata {} ETA {}",
+            "#".repeat(filled),
+            ".".repeat(BAR_WIDTH - filled),
+            fraction * 100.0,
+            step,
+            self.total,
+            loss.map_or("-".to_string(), |l| format!("{:.6}", l)),
+            eta
+        )
+    }
+}
+
+/// Czas w sekundach jako `mm:ss` lub `hh:mm:ss`.
+fn format_duration(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    let (h, m, s) = (total / 3600, total / 60 % 60, total % 60);
+    if h > 0 {
+        format!("{:02}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{:02}:{:02}", m, s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_data, generate_training_data, MLP, RNG_SEED};
+    use std::fs;
+
+    #[test]
+    fn test_telemetry_log_and_progress_bar() {
+        let data = generate_data(100, 0.0, 3.0, RNG_SEED);
+        let targets = generate_training_data(&data);
+
+        for format in [TelemetryFormat::JsonLines, TelemetryFormat::Csv] {
+            let path = std::env::temp_dir().join(format!("mlp_telemetry_{}_{:?}", std::process::id(), format));
+            let mut mlp = MLP::new(2, 5, 1, 1000, 0.1, 1);
+            mlp.config.telemetry_every = Some(100);
+            mlp.config.telemetry_path = path.to_string_lossy().into_owned();
+            mlp.config.telemetry_format = format;
+            mlp.fit(&data, &targets);
+
+            let content = fs::read_to_string(&path).unwrap();
+            let records: Vec<TelemetryRecord> = match format {
+                TelemetryFormat::JsonLines => content.lines().map(|l| serde_json::from_str(l).unwrap()).collect(),
+                TelemetryFormat::Csv => {
+                    let mut rdr = csv::Reader::from_path(&path).unwrap();
+                    rdr.deserialize().map(|r| r.unwrap()).collect()
+                }
+            };
+            assert_eq!(records.len(), 10);
+            assert_eq!(records.last().unwrap().step, 1000);
+            assert!(records.iter().all(|r| r.gradient_norm > 0.0 && r.hidden_weight_norm > 0.0));
+            assert!(records.iter().all(|r| r.learning_rate == 0.1 && r.validation_loss.is_none()));
+            assert!(records.last().unwrap().train_loss < records[0].train_loss);
+            fs::remove_file(&path).unwrap();
+        }
+
+        let bar = ProgressBar::new(0, 1000);
+        assert_eq!(
+            bar.render(500, Some(0.5), Duration::from_secs(10)),
+            format!("[{}{}]  50% 500/1000 strata 0.500000 ETA 00:10", "#".repeat(15), ".".repeat(15))
+        );
+        assert_eq!(format_duration(3725.0), "01:02:05");
+    }
+}
@@ -0,0 +1,157 @@
+use super::*;
+use crate::scaling::Scaling;
+
+#[test]
+fn test_same_seed_gives_identical_model() {
+    let data: Vec<Vec<f64>> = generate_data(50, 0.0, PI, RNG_SEED);
+    let training_data: Vec<f64> = generate_training_data(&data);
+
+    let train = |seed: u64| {
+        let mut mlp = MLP::new(2, 5, 1, 200, 0.1, seed);
+        mlp.fit(&data, &training_data);
+        mlp
+    };
+
+    let first = train(MODEL_RNG_SEED);
+    let second = train(MODEL_RNG_SEED);
+
+    // Wagi i predykcje muszą być identyczne bit po bicie
+    let bits = |mlp: &MLP| -> Vec<u64> {
+        mlp.parameters().iter().map(|w| w.to_bits()).collect()
+    };
+    assert_eq!(bits(&first), bits(&second));
+
+    let first_prediction: Vec<u64> = first.predict(&data).iter().map(|y| y.to_bits()).collect();
+    let second_prediction: Vec<u64> = second.predict(&data).iter().map(|y| y.to_bits()).collect();
+    assert_eq!(first_prediction, second_prediction);
+
+    // Inne ziarno powinno dać inną sieć
+    let other = train(MODEL_RNG_SEED + 1);
+    assert_ne!(bits(&first), bits(&other));
+}
+
+#[test]
+fn test_early_stopping_restores_best_weights() {
+    let data: Vec<Vec<f64>> = generate_data(100, 0.0, PI, RNG_SEED);
+    let training_data: Vec<f64> = generate_training_data(&data);
+
+    let early_stopping_mlp = |restore_best_weights: bool| {
+        let mut mlp = MLP::new(2, 5, 1, 1_000_000, 0.5, MODEL_RNG_SEED);
+        mlp.config.validation_split = 0.2;
+        mlp.config.eval_every = 50;
+        mlp.config.patience = Some(3);
+        mlp.config.restore_best_weights = restore_best_weights;
+        mlp
+    };
+    let mut mlp = early_stopping_mlp(true);
+    let history = mlp.fit(&data, &training_data);
+
+    assert!(history.stopped_early);
+    assert!(*history.steps.last().unwrap() < mlp.learning_steps);
+    assert_eq!(history.steps.len(), history.train_loss.len());
+    assert_eq!(history.steps.len(), history.validation_loss.len());
+
+    // Po zakończeniu model ma wagi z najmniejszą stratą walidacyjną
+    let best_loss = history.validation_loss.iter().cloned().fold(f64::INFINITY, f64::min);
+    let best_index = history.validation_loss.iter().position(|&l| l == best_loss).unwrap();
+    assert_eq!(history.best_step, Some(history.steps[best_index]));
+    assert_eq!(history.validation_loss.len() - 1 - best_index, 3);
+
+    // Bez przywracania model zostaje z wagami z ostatniego kroku
+    let mut last_mlp = early_stopping_mlp(false);
+    let last_history = last_mlp.fit(&data, &training_data);
+    assert_eq!(last_history.validation_loss, history.validation_loss);
+    assert_ne!(last_mlp.parameters(), mlp.parameters());
+}
+
+#[test]
+fn test_regularization() {
+    let data = generate_data(100, 0.0, PI, RNG_SEED);
+    let targets = generate_training_data(&data);
+    let norm = |mlp: &MLP| mlp.parameters().iter().map(|w| w * w).sum::<f64>().sqrt();
+
+    let mut plain = MLP::new(2, 10, 1, 5000, 0.1, MODEL_RNG_SEED);
+    plain.fit(&data, &targets);
+    let mut l2 = MLP::new(2, 10, 1, 5000, 0.1, MODEL_RNG_SEED);
+    l2.config.l2 = 0.05;
+    l2.fit(&data, &targets);
+    assert!(norm(&l2) < norm(&plain));
+
+    // Dropout działa tylko w trakcie uczenia, predykcja jest deterministyczna
+    let mut dropout = MLP::new(2, 10, 1, 2000, 0.1, MODEL_RNG_SEED);
+    dropout.config.dropout = 0.5;
+    dropout.fit(&data, &targets);
+    assert_eq!(dropout.predict(&data), dropout.predict(&data));
+
+    // Obcięty gradient zmienia wagi co najwyżej o `learning_rate * clip_norm`
+    let mut clipped = MLP::new(2, 10, 1, 1, 0.1, MODEL_RNG_SEED);
+    clipped.config.clip_norm = Some(1e-3);
+    let before = clipped.parameters();
+    clipped.train_step(&[&data[0]], &[&[100.0]], 0.1);
+    let step: f64 = clipped
+        .parameters()
+        .iter()
+        .zip(before.iter())
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt();
+    assert!(step > 0.0 && step <= 1e-4 + 1e-12);
+}
+
+#[test]
+fn test_batched_pass_matches_single_samples() {
+    let data = generate_data(64, 0.0, PI, RNG_SEED);
+    let targets: Vec<Vec<f64>> = generate_training_data(&data).into_iter().map(|t| vec![t]).collect();
+    let mut mlp = MLP::new(2, 7, 1, 0, 0.1, MODEL_RNG_SEED);
+
+    let single: Vec<f64> = data.iter().map(|x| mlp.forward_propagation(x).1[0]).collect();
+    assert_eq!(mlp.predict(&data), single);
+
+    // Równoległe obliczenia dają wynik identyczny z sekwencyjnymi
+    let inputs: Vec<&[f64]> = data.iter().map(Vec::as_slice).collect();
+    let batch_targets: Vec<&[f64]> = targets.iter().map(Vec::as_slice).collect();
+    let serial = mlp.gradient(&inputs, &batch_targets, None);
+    mlp.config.parallel = true;
+    assert_eq!(mlp.predict(&data), single);
+    assert_eq!(mlp.gradient(&inputs, &batch_targets, None), serial);
+
+    // Gradient wsadu to średnia gradientów pojedynczych próbek
+    let mean: Vec<f64> = inputs
+        .iter()
+        .zip(batch_targets.iter())
+        .map(|(x, t)| mlp.gradient(&[x], &[t], None))
+        .fold(vec![0.0; serial.len()], |acc, g| acc.iter().zip(g.iter()).map(|(a, b)| a + b).collect())
+        .iter()
+        .map(|g| g / inputs.len() as f64)
+        .collect();
+    assert!(mean.iter().zip(serial.iter()).all(|(a, b)| (a - b).abs() < 1e-12));
+}
+
+#[test]
+fn test_scalers_are_fitted_and_saved_with_model() {
+    // Wejścia daleko od zera i duże wartości docelowe
+    let data: Vec<Vec<f64>> = generate_data(200, 0.0, PI, RNG_SEED)
+        .into_iter()
+        .map(|x| vec![x[0] + 1000.0, x[1] * 100.0])
+        .collect();
+    let targets: Vec<f64> = data.iter().map(|x| 500.0 + 100.0 * (x[0] - 1000.0).sin()).collect();
+
+    let mut mlp = MLP::new(2, 10, 1, 20_000, 0.1, MODEL_RNG_SEED);
+    mlp.config.input_scaling = Scaling::Standard;
+    mlp.config.target_scaling = Scaling::MinMax;
+    mlp.fit(&data, &targets);
+    assert_eq!(mlp.input_scaler.as_ref().unwrap().scaling, Scaling::Standard);
+
+    // Predykcje są w skali oryginalnych wartości docelowych
+    let predictions = mlp.predict(&data);
+    let mse = Loss::MSE.mean(&predictions, &targets);
+    let variance = Loss::MSE.mean(&targets, &vec![500.0 + 100.0 * 2.0 / PI; targets.len()]);
+    assert!(mse < 0.1 * variance, "MSE {} przy wariancji {}", mse, variance);
+
+    let path = std::env::temp_dir().join(format!("mlp_scaled_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    mlp.save(path).unwrap();
+    let loaded = MLP::load(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(loaded.predict(&data), predictions);
+}
@@ -0,0 +1,111 @@
+use std::f64::consts::PI;
+use std::hint::black_box;
+use std::time::Instant;
+
+use crate::{generate_data, generate_training_data, MLP, MODEL_RNG_SEED, RNG_SEED};
+
+/// Liczba próbek w pomiarach predykcji.
+const PREDICT_SAMPLES: usize = 20_000;
+/// Liczba próbek w pomiarach uczenia.
+const TRAIN_SAMPLES: usize = 20_000;
+
+/// Krok uczenia w pierwotnej postaci: propagacja w przód powtarzana dla każdego neuronu
+/// i aktualizacja wag neuronu tuż po niej. Służy jako punkt odniesienia w pomiarach.
+fn per_neuron_train_step(mlp: &mut MLP, input: &[f64], target: &[f64], learning_rate: f64) {
+    let n_inputs = mlp.n_inputs();
+    for n in 0..mlp.N {
+        let (activation_values, y) = mlp.forward_propagation(input);
+        let errors: Vec<f64> = mlp.output_deltas(&y, target);
+        let back_error: f64 = errors
+            .iter()
+            .enumerate()
+            .map(|(k, e_k)| e_k * mlp.output_layer_weights.row(k)[n])
+            .sum();
+        let a_n = activation_values[n];
+        for (v_i, x_i) in mlp.hidden_layer_weights.row_mut(n)[..n_inputs]
+            .iter_mut()
+            .zip(input.iter())
+        {
+            *v_i -= learning_rate * back_error * a_n * (1.0 - a_n) * x_i;
+        }
+        for (k, e_k) in errors.iter().enumerate() {
+            mlp.output_layer_weights.row_mut(k)[n] -= learning_rate * e_k * a_n;
+        }
+    }
+}
+
+/// Mierzy czas wykonania `f` i zwraca liczbę próbek na sekundę.
+fn samples_per_second(samples: usize, f: impl FnOnce()) -> f64 {
+    let start = Instant::now();
+    f();
+    samples as f64 / start.elapsed().as_secs_f64()
+}
+
+/// Porównuje przepustowość predykcji i uczenia: pierwotnej implementacji próbka po próbce
+/// oraz wsadowej, sekwencyjnie i równolegle (rayon), dla kilku rozmiarów warstwy ukrytej.
+pub(crate) fn throughput_benchmark() {
+    let data = generate_data(PREDICT_SAMPLES.max(TRAIN_SAMPLES), 0.0, PI, RNG_SEED);
+    let targets: Vec<Vec<f64>> = generate_training_data(&data).into_iter().map(|t| vec![t]).collect();
+
+    println!("{:<36} {:>5} {:>14}", "Wariant", "N", "próbek/s");
+    for N in [20, 100, 500] {
+        let mut results: Vec<(&str, f64)> = Vec::new();
+        let mut mlp = MLP::new(2, N, 1, 0, 0.01, MODEL_RNG_SEED);
+
+        // Predykcja
+        let predict_data = &data[..PREDICT_SAMPLES];
+        results.push((
+            "predykcja, próbka po próbce",
+            samples_per_second(PREDICT_SAMPLES, || {
+                for x in predict_data {
+                    black_box(mlp.forward_propagation(x).1[0]);
+                }
+            }),
+        ));
+        results.push((
+            "predykcja, wsadowo",
+            samples_per_second(PREDICT_SAMPLES, || {
+                black_box(mlp.predict(predict_data));
+            }),
+        ));
+        mlp.config.parallel = true;
+        results.push((
+            "predykcja, wsadowo równolegle",
+            samples_per_second(PREDICT_SAMPLES, || {
+                black_box(mlp.predict(predict_data));
+            }),
+        ));
+        mlp.config.parallel = false;
+
+        // Uczenie
+        results.push((
+            "uczenie, propagacja na neuron",
+            samples_per_second(TRAIN_SAMPLES, || {
+                for (x, t) in data.iter().zip(targets.iter()).take(TRAIN_SAMPLES) {
+                    per_neuron_train_step(&mut mlp, x, t, 0.01);
+                }
+            }),
+        ));
+        for (name, batch_size, parallel) in [
+            ("uczenie, wsad 1", 1, false),
+            ("uczenie, wsad 64", 64, false),
+            ("uczenie, wsad 64 równolegle", 64, true),
+        ] {
+            mlp.config.parallel = parallel;
+            let inputs: Vec<&[f64]> = data.iter().take(TRAIN_SAMPLES).map(Vec::as_slice).collect();
+            let batch_targets: Vec<&[f64]> = targets.iter().take(TRAIN_SAMPLES).map(Vec::as_slice).collect();
+            results.push((
+                name,
+                samples_per_second(TRAIN_SAMPLES, || {
+                    for (x, t) in inputs.chunks(batch_size).zip(batch_targets.chunks(batch_size)) {
+                        mlp.train_step(x, t, 0.01);
+                    }
+                }),
+            ));
+        }
+
+        for (name, throughput) in results {
+            println!("{:<36} {:>5} {:>14.0}", name, N, throughput);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use csv::Writer;
+use serde::{Deserialize, Serialize};
+
+use crate::scaling::Scaling;
+use crate::schedule::{Schedule, Scheduler};
+use crate::telemetry::TelemetryFormat;
+
+/// Dodatkowe ustawienia uczenia, uzupełniające liczbę kroków i współczynnik uczenia.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct TrainingConfig {
+    pub(crate) validation_split: f64,             // Część danych odkładana do walidacji (0.0 - brak)
+    pub(crate) eval_every: usize,                 // Co ile kroków liczona jest strata
+    pub(crate) patience: Option<usize>,           // Liczba ocen bez poprawy przed zatrzymaniem
+    pub(crate) restore_best_weights: bool,        // Przywrócenie wag z najlepszą stratą walidacyjną
+    pub(crate) schedule: Schedule,                // Harmonogram współczynnika uczenia
+    pub(crate) warmup_steps: usize,               // Liczba kroków liniowego rozgrzewania
+    pub(crate) l1: f64,                           // Współczynnik kary L1 za wagi (0.0 - brak)
+    pub(crate) l2: f64,                           // Współczynnik kary L2 za wagi (0.0 - brak)
+    pub(crate) dropout: f64,                      // Prawdopodobieństwo wyłączenia neuronu ukrytego (0.0 - brak)
+    pub(crate) clip_norm: Option<f64>,            // Maksymalna norma gradientu
+    pub(crate) batch_size: usize,                 // Liczba próbek w jednym kroku uczenia
+    pub(crate) parallel: bool,                    // Równoległe obliczenia na wierszach wsadu (rayon)
+    pub(crate) input_scaling: Scaling,            // Skalowanie wejść dopasowywane do zbioru uczącego
+    pub(crate) target_scaling: Scaling,           // Skalowanie wartości docelowych (tylko regresja)
+    pub(crate) checkpoint_every: Option<usize>,   // Co ile kroków zapisywany jest punkt kontrolny
+    pub(crate) checkpoint_path: String,           // Ścieżka pliku punktu kontrolnego
+    pub(crate) telemetry_every: Option<usize>,    // Co ile kroków zapisywana jest telemetria
+    pub(crate) telemetry_path: String,            // Ścieżka pliku telemetrii
+    pub(crate) telemetry_format: TelemetryFormat, // Format pliku telemetrii
+    pub(crate) progress: bool,                    // Pasek postępu z szacowanym czasem na stderr
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        TrainingConfig {
+            validation_split: 0.0,
+            eval_every: 1000,
+            patience: None,
+            restore_best_weights: true,
+            schedule: Schedule::Constant,
+            warmup_steps: 0,
+            l1: 0.0,
+            l2: 0.0,
+            dropout: 0.0,
+            clip_norm: None,
+            batch_size: 1,
+            parallel: false,
+            input_scaling: Scaling::None,
+            target_scaling: Scaling::None,
+            checkpoint_every: None,
+            checkpoint_path: "checkpoint.json".to_string(),
+            telemetry_every: None,
+            telemetry_path: "telemetry.jsonl".to_string(),
+            telemetry_format: TelemetryFormat::JsonLines,
+            progress: false,
+        }
+    }
+}
+
+/// Przebieg uczenia: strata uczenia i walidacji oraz współczynnik uczenia,
+/// zapisywane co `eval_every` kroków.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct TrainingHistory {
+    pub(crate) steps: Vec<usize>,
+    pub(crate) train_loss: Vec<f64>,
+    pub(crate) validation_loss: Vec<f64>, // Pusty, gdy nie wydzielono zbioru walidacyjnego
+    pub(crate) learning_rate: Vec<f64>,
+    pub(crate) best_step: Option<usize>,  // Krok z najmniejszą stratą walidacyjną
+    pub(crate) stopped_early: bool,
+}
+
+impl TrainingHistory {
+    /// Zapisuje historię do pliku CSV z nagłówkiem `step,train_loss,validation_loss,learning_rate`.
+    pub(crate) fn save_csv(&self, path: &str) -> Result<(), csv::Error> {
+        let mut wtr = Writer::from_path(path)?;
+        wtr.write_record(["step", "train_loss", "validation_loss", "learning_rate"])?;
+        for (i, (step, train_loss)) in self.steps.iter().zip(self.train_loss.iter()).enumerate() {
+            let validation_loss = self
+                .validation_loss
+                .get(i)
+                .map_or(String::new(), |v| v.to_string());
+            wtr.write_record([
+                step.to_string(),
+                train_loss.to_string(),
+                validation_loss,
+                self.learning_rate[i].to_string(),
+            ])?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+/// Stan przerwanego uczenia, zapisywany w punkcie kontrolnym razem z modelem.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct TrainingState<F = f64> {
+    pub(crate) step: usize,                     // Liczba wykonanych kroków uczenia
+    pub(crate) indices: Vec<usize>,             // Kolejność próbek; początkowe tworzą zbiór walidacyjny
+    pub(crate) scheduler: Scheduler,            // Stan harmonogramu współczynnika uczenia
+    pub(crate) history: TrainingHistory,        // Dotychczasowa historia uczenia
+    pub(crate) best: Option<(f64, Vec<F>)>,     // Najmniejsza strata walidacyjna i wagi, przy których wystąpiła
+    pub(crate) evaluations_without_improvement: usize,
+}
+
+impl<F> TrainingState<F> {
+    /// Stan na początku uczenia.
+    pub(crate) fn new(indices: Vec<usize>, scheduler: Scheduler) -> Self {
+        TrainingState {
+            step: 0,
+            indices,
+            scheduler,
+            history: TrainingHistory::default(),
+            best: None,
+            evaluations_without_improvement: 0,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use rand::seq::SliceRandom;
 use rand::thread_rng;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
 use chrono::{DateTime, Local, Datelike, Timelike};
@@ -10,12 +10,32 @@ macro_rules! split_result {
     () => {(Vec<usize>, Vec<usize>, Vec<Vec<f64>>, Vec<Vec<f64>>)};
 }
 
+/// Wariant klasyfikatora porównywany w `benchmark()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Model {
+    /// `NBC` na cechach zdyskretyzowanych przez `k_bins_discretizer`.
+    Binned { alpha: f64, logarithmic: bool },
+    /// `GaussianNB` na surowych cechach ciągłych.
+    Gaussian { var_smoothing: f64 },
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Model::Binned { alpha, logarithmic } => {
+                write!(f, "binned, alpha: {}, logarithmic: {}", alpha, logarithmic)
+            }
+            Model::Gaussian { var_smoothing } => write!(f, "gaussian, var_smoothing: {:e}", var_smoothing),
+        }
+    }
+}
+
 struct Stats {
     loop_count: usize,
     n_bins_size: usize,
     data_type: String,
     dataset: String,
-    accuracy_vec_average: Vec<((bool,bool), f64)>,
+    accuracy_vec_average: Vec<(Model, f64)>,
 }
 
 impl Stats {
@@ -33,13 +53,14 @@ impl Stats {
 impl fmt::Display for Stats {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "{} - {}", self.data_type, self.dataset)?;
-        for (settings, avg) in self.accuracy_vec_average.iter() {
-            writeln!(f, "laplace_smoothing: {}, logarithmic: {}, accuracy: {:.2}%", settings.0, settings.1, avg*100.)?;
+        for (model, avg) in self.accuracy_vec_average.iter() {
+            writeln!(f, "{}, accuracy: {:.2}%", model, avg*100.)?;
         }
         Ok(())
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 struct NBC {
     n_bins: usize,
     separate_classes: HashMap<usize, Vec<Vec<f64>>>,
@@ -57,7 +78,16 @@ impl NBC {
         }
     }
 
-    fn fit(&mut self, classes: &[usize], data: &[Vec<f64>], laplace_smoothing: bool) {
+    /// Uczy klasyfikator z wygładzaniem addytywnym (Lidstone'a): prawdopodobieństwo przedziału
+    /// to `(count + alpha) / (len + alpha * n_bins)`, więc rozkład każdej cechy w klasie sumuje
+    /// się do 1. `alpha = 0` wyłącza wygładzanie, a `alpha = 1` to wygładzanie Laplace'a.
+    ///
+    /// Przy `alpha = 0` przedział bez próbek ma prawdopodobieństwo 0, więc w trybie
+    /// logarytmicznym `predict` dodaje `ln(0) = -inf` i klasa nie może zostać wybrana.
+    /// Panikuje, gdy `alpha` jest ujemne lub nie jest liczbą.
+    fn fit(&mut self, classes: &[usize], data: &[Vec<f64>], alpha: f64) {
+        assert!(alpha >= 0.0, "alpha musi być nieujemne, podano {}", alpha);
+
         for (&class, data_val) in classes.iter().zip(data.iter()) {
             self.separate_classes
                 .entry(class)
@@ -69,12 +99,8 @@ impl NBC {
             for n_bin in 0..self.n_bins {
                 for i in 0..data[0].len() {
                     let count = data.iter().filter(|item| item[i] == n_bin as f64).count();
-                    let probability = if !laplace_smoothing {
-                        count as f64 / data.len() as f64
-                    } else {
-                        let k = 1;
-                        (count + k) as f64 / (data.len() + k) as f64
-                    };
+                    let probability =
+                        (count as f64 + alpha) / (data.len() as f64 + alpha * self.n_bins as f64);
                     self.probabilities.insert((*class, i, n_bin), probability);
                 }
             }
@@ -125,6 +151,8 @@ impl NBC {
         predicted_classes
     }
 
+    // Używane w zakomentowanych eksperymentach w `main`
+    #[allow(dead_code)]
     fn predict_proba(&self, data: &[Vec<f64>], logarithmic: bool) -> Vec<Vec<f64>> {
         let mut classes_prob: Vec<Vec<f64>> = Vec::new();
 
@@ -159,6 +187,111 @@ impl NBC {
     }
 }
 
+/// Naiwny klasyfikator Bayesa dla cech ciągłych: każda cecha w każdej klasie ma rozkład
+/// normalny o średniej i wariancji estymowanych ze zbioru uczącego.
+struct GaussianNB {
+    var_smoothing: f64,        // Część największej wariancji cechy dodawana do wszystkich wariancji
+    classes: Vec<usize>,       // Klasy w kolejności rosnącej
+    log_priors: Vec<f64>,      // Logarytm prawdopodobieństwa a priori każdej klasy
+    means: Vec<Vec<f64>>,      // Średnia każdej cechy, jeden wektor na klasę
+    variances: Vec<Vec<f64>>,  // Wariancja każdej cechy (z wygładzeniem), jeden wektor na klasę
+}
+
+impl GaussianNB {
+    fn new(var_smoothing: f64) -> Self {
+        GaussianNB {
+            var_smoothing,
+            classes: Vec::new(),
+            log_priors: Vec::new(),
+            means: Vec::new(),
+            variances: Vec::new(),
+        }
+    }
+
+    /// Uczy klasyfikator: dla każdej klasy wyznacza prawdopodobieństwo a priori oraz średnią
+    /// i wariancję każdej cechy. Do wariancji dodawane jest `var_smoothing` razy największa
+    /// wariancja cechy w całym zbiorze, nie mniej niż `f64::EPSILON`.
+    fn fit(&mut self, classes: &[usize], data: &[Vec<f64>]) {
+        let mut separate_classes: BTreeMap<usize, Vec<&Vec<f64>>> = BTreeMap::new();
+        for (&class, data_val) in classes.iter().zip(data.iter()) {
+            separate_classes.entry(class).or_default().push(data_val);
+        }
+
+        // Wygładzenie chroni przed zerową wariancją cechy stałej w klasie; dolne ograniczenie
+        // obejmuje zbiór, w którym wszystkie cechy są stałe
+        let all: Vec<&Vec<f64>> = data.iter().collect();
+        let (_, all_variances) = mean_and_variance(&all);
+        let epsilon = (self.var_smoothing * all_variances.iter().cloned().fold(0.0, f64::max)).max(f64::EPSILON);
+
+        self.classes.clear();
+        self.log_priors.clear();
+        self.means.clear();
+        self.variances.clear();
+        for (class, rows) in separate_classes.iter() {
+            let (mean, variance) = mean_and_variance(rows);
+            self.classes.push(*class);
+            self.log_priors.push((rows.len() as f64 / data.len() as f64).ln());
+            self.means.push(mean);
+            self.variances.push(variance.iter().map(|v| v + epsilon).collect());
+        }
+    }
+
+    /// Logarytm łącznej wiarygodności `ln P(c) + Σ_i ln N(x_i; mean_ci, var_ci)` każdej klasy.
+    fn joint_log_likelihood(&self, x: &[f64]) -> Vec<f64> {
+        (0..self.classes.len())
+            .map(|c| {
+                self.log_priors[c]
+                    + x.iter()
+                        .zip(self.means[c].iter().zip(self.variances[c].iter()))
+                        .map(|(v, (mean, var))| {
+                            -0.5 * ((2.0 * std::f64::consts::PI * var).ln() + (v - mean).powi(2) / var)
+                        })
+                        .sum::<f64>()
+            })
+            .collect()
+    }
+
+    fn predict(&self, data: &[Vec<f64>]) -> Vec<usize> {
+        data.iter()
+            .map(|x| {
+                let log_likelihood = self.joint_log_likelihood(x);
+                let best = (0..self.classes.len())
+                    .max_by(|&a, &b| log_likelihood[a].total_cmp(&log_likelihood[b]))
+                    .unwrap();
+                self.classes[best]
+            })
+            .collect()
+    }
+
+    /// Prawdopodobieństwa a posteriori klas (w kolejności `classes`), normalizowane
+    /// w przestrzeni logarytmów, żeby uniknąć niedomiaru.
+    #[cfg(test)]
+    fn predict_proba(&self, data: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        data.iter()
+            .map(|x| {
+                let log_likelihood = self.joint_log_likelihood(x);
+                let max = log_likelihood.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                let log_sum = max + log_likelihood.iter().map(|l| (l - max).exp()).sum::<f64>().ln();
+                log_likelihood.iter().map(|l| (l - log_sum).exp()).collect()
+            })
+            .collect()
+    }
+}
+
+/// Średnia i wariancja (obciążona) każdej kolumny.
+fn mean_and_variance(rows: &[&Vec<f64>]) -> (Vec<f64>, Vec<f64>) {
+    let n = rows.len() as f64;
+    let mean: Vec<f64> = (0..rows[0].len())
+        .map(|i| rows.iter().map(|row| row[i]).sum::<f64>() / n)
+        .collect();
+    let variance = mean
+        .iter()
+        .enumerate()
+        .map(|(i, m)| rows.iter().map(|row| (row[i] - m).powi(2)).sum::<f64>() / n)
+        .collect();
+    (mean, variance)
+}
+
 fn load_from_txt(path: &str, delimiter: &str) -> (Vec<usize>, Vec<Vec<f64>>) {
     let mut classes: Vec<usize> = Vec::new();
     let mut data: Vec<Vec<f64>> = Vec::new();
@@ -198,42 +331,39 @@ fn k_bins_discretizer(data: &mut [Vec<f64>], n_bins: usize) {
     }
 }
 
-fn train_test_split(
-    data: &mut [Vec<f64>],
-    classes: &mut [usize],
-    test_size: f64,
-) -> split_result!() {
-    let n = data.len();
+fn train_test_indices(n: usize, test_size: f64) -> (Vec<usize>, Vec<usize>) {
     let test_size = (test_size * n as f64) as usize;
 
     let mut rng = thread_rng();
+    let mut indices: Vec<usize> = (0..n).collect();
+    indices.shuffle(&mut rng);
 
-    let mut data_classes: Vec<_> = data.iter().zip(classes.iter()).collect();
-    data_classes.shuffle(&mut rng);
-
-    let (shuffled_data, shuffled_classes): (Vec<Vec<f64>>, Vec<usize>) = data_classes
-        .into_iter()
-        .map(|(d, c)| (d.clone(), c))
-        .unzip();
-
-    let mut train_data: Vec<Vec<f64>> = Vec::new();
-    let mut train_classes: Vec<usize> = Vec::new();
-    let mut test_data: Vec<Vec<f64>> = Vec::new();
-    let mut test_classes: Vec<usize> = Vec::new();
-
-    for i in 0..n {
-        if i < test_size {
-            test_data.push(shuffled_data[i].clone());
-            test_classes.push(shuffled_classes[i]);
-        } else {
-            train_data.push(shuffled_data[i].clone());
-            train_classes.push(shuffled_classes[i]);
-        }
-    }
+    let train_indices = indices.split_off(test_size);
+    (train_indices, indices)
+}
 
-    (train_classes, test_classes, train_data, test_data)
+fn select_rows<T: Clone>(data: &[T], indices: &[usize]) -> Vec<T> {
+    indices.iter().map(|&i| data[i].clone()).collect()
 }
 
+// Używane w zakomentowanych eksperymentach w `main`
+#[allow(dead_code)]
+fn train_test_split(
+    data: &mut [Vec<f64>],
+    classes: &mut [usize],
+    test_size: f64,
+) -> split_result!() {
+    let (train_indices, test_indices) = train_test_indices(data.len(), test_size);
+
+    (
+        select_rows(classes, &train_indices),
+        select_rows(classes, &test_indices),
+        select_rows(data, &train_indices),
+        select_rows(data, &test_indices),
+    )
+}
+
+#[allow(dead_code)]
 fn print_data(data: &[Vec<f64>]) {
     if data.len() > 20 {
         // Print first 10 and last 10 rows
@@ -286,6 +416,9 @@ fn benchmark(dataset: &str, print: bool, save: bool) {
 
     const N_BINS: usize = 5;
     const LOOP_COUNT: usize = 100;
+    const VAR_SMOOTHING: f64 = 1e-9;
+    // Bez wygładzania, wygładzanie Lidstone'a i Laplace'a
+    const ALPHAS: [f64; 3] = [0.0, 0.5, 1.0];
 
     test_stats.n_bins_size = N_BINS;
     test_stats.loop_count = LOOP_COUNT;
@@ -293,30 +426,25 @@ fn benchmark(dataset: &str, print: bool, save: bool) {
     train_stats.loop_count = LOOP_COUNT;
     
     
-    let (mut classes, mut data) = load_from_txt(dataset, ",");
+    let (classes, raw_data) = load_from_txt(dataset, ",");
+    let mut data = raw_data.clone();
 
     k_bins_discretizer(&mut data, N_BINS);
 
-    let (train_classes, test_classes, train_data, test_data) =
-        train_test_split(&mut data, &mut classes, 0.3);
-
-    let mut laplace_smoothing: bool;
-    let mut logarithmic: bool;
+    // Ten sam podział dla cech zdyskretyzowanych i surowych
+    let (train_indices, test_indices) = train_test_indices(data.len(), 0.3);
+    let train_classes = select_rows(&classes, &train_indices);
+    let test_classes = select_rows(&classes, &test_indices);
+    let train_data = select_rows(&data, &train_indices);
+    let test_data = select_rows(&data, &test_indices);
 
-    for j in 0..4 {
-        match j {
-            0 => {laplace_smoothing = false; logarithmic = false;}
-            1 => {laplace_smoothing = true; logarithmic = false;}
-            2 => {laplace_smoothing = false; logarithmic = true;}
-            3 => {laplace_smoothing = true; logarithmic = true;}
-            _ => {laplace_smoothing = false; logarithmic = false;}
-        }
+    for (alpha, logarithmic) in ALPHAS.iter().flat_map(|&alpha| [(alpha, false), (alpha, true)]) {
         let mut test_accuracy_vec: Vec<f64> = vec![0.0; LOOP_COUNT];
         let mut train_accuracy_vec: Vec<f64> = vec![0.0; LOOP_COUNT];
         for (test_item, train_item) in test_accuracy_vec.iter_mut().take(LOOP_COUNT).zip(train_accuracy_vec.iter_mut().take(LOOP_COUNT)) {
 
             let mut nbc = NBC::new(N_BINS);
-            nbc.fit(&train_classes, &train_data, laplace_smoothing);
+            nbc.fit(&train_classes, &train_data, alpha);
 
             let classes_predicted: Vec<usize> = nbc.predict(&test_data, logarithmic);
             let correct_matches = return_correct_matches(&classes_predicted, &test_classes);
@@ -324,7 +452,7 @@ fn benchmark(dataset: &str, print: bool, save: bool) {
             *test_item = accuracy;
 
             let mut nbc = NBC::new(N_BINS);
-            nbc.fit(&train_classes, &train_data, laplace_smoothing);
+            nbc.fit(&train_classes, &train_data, alpha);
             let classes_predicted: Vec<usize> = nbc.predict(&train_data, logarithmic);
             let correct_matches = return_correct_matches(&classes_predicted, &train_classes);
             let accuracy = correct_matches as f64 / classes_predicted.len() as f64;
@@ -334,10 +462,26 @@ fn benchmark(dataset: &str, print: bool, save: bool) {
         let test_average_accuracy = test_accuracy_vec.iter().sum::<f64>() / test_accuracy_vec.len() as f64;
         let train_average_accuracy = train_accuracy_vec.iter().sum::<f64>() / train_accuracy_vec.len() as f64;
 
-        test_stats.accuracy_vec_average.push(((laplace_smoothing, logarithmic), test_average_accuracy));
-        train_stats.accuracy_vec_average.push(((laplace_smoothing, logarithmic), train_average_accuracy));
+        let model = Model::Binned { alpha, logarithmic };
+        test_stats.accuracy_vec_average.push((model, test_average_accuracy));
+        train_stats.accuracy_vec_average.push((model, train_average_accuracy));
     }
 
+    // Gaussian NB na surowych cechach; uczenie jest deterministyczne, więc wystarcza jedna próba
+    let raw_train_data = select_rows(&raw_data, &train_indices);
+    let raw_test_data = select_rows(&raw_data, &test_indices);
+    let mut gnb = GaussianNB::new(VAR_SMOOTHING);
+    gnb.fit(&train_classes, &raw_train_data);
+
+    let classes_predicted: Vec<usize> = gnb.predict(&raw_test_data);
+    let test_accuracy = return_correct_matches(&classes_predicted, &test_classes) as f64 / classes_predicted.len() as f64;
+    let classes_predicted: Vec<usize> = gnb.predict(&raw_train_data);
+    let train_accuracy = return_correct_matches(&classes_predicted, &train_classes) as f64 / classes_predicted.len() as f64;
+
+    let model = Model::Gaussian { var_smoothing: VAR_SMOOTHING };
+    test_stats.accuracy_vec_average.push((model, test_accuracy));
+    train_stats.accuracy_vec_average.push((model, train_accuracy));
+
     if print {
         println!("{test_stats}");
         println!("{train_stats}");
@@ -351,11 +495,11 @@ fn benchmark(dataset: &str, print: bool, save: bool) {
 fn main() {
     benchmark("wine.data", true, false);
 
-    // benchmark("abalone.data", true, false);
+    benchmark("abalone.data", true, false);
 
     // let (mut classes, mut data) = load_from_txt("wine.data", ",");
     // let n_bins: usize = 5;
-    // let laplace_smoothing: bool = false;
+    // let alpha: f64 = 0.0;
     // let logarithmic: bool = false;
 
     // k_bins_discretizer(&mut data, n_bins);
@@ -369,7 +513,7 @@ fn main() {
     // println!("********************************\n");
 
     // let mut nbc = NBC::new(n_bins);
-    // nbc.fit(&train_classes, &train_data, laplace_smoothing);
+    // nbc.fit(&train_classes, &train_data, alpha);
 
     // // Test predict_proba i predict na danych uczonych
     // let classes_prob: Vec<Vec<f64>> = nbc.predict_proba(&train_data, logarithmic);
@@ -402,9 +546,9 @@ fn main() {
     // println!("*   logharithmic      = false  *");
     // println!("********************************\n");
 
-    // let laplace_smoothing: bool = true;
+    // let alpha: f64 = 1.0;
     // let mut nbc = NBC::new(n_bins);
-    // nbc.fit(&train_classes, &train_data, laplace_smoothing);
+    // nbc.fit(&train_classes, &train_data, alpha);
 
     // // Test predict_proba i predict na danych uczonych
     // let classes_prob: Vec<Vec<f64>> = nbc.predict_proba(&train_data, logarithmic);
@@ -438,10 +582,10 @@ fn main() {
     // println!("********************************\n");
 
     // let logarithmic: bool = true;
-    // let laplace_smoothing: bool = false;
+    // let alpha: f64 = 0.0;
 
     // let mut nbc = NBC::new(n_bins);
-    // nbc.fit(&train_classes, &train_data, laplace_smoothing);
+    // nbc.fit(&train_classes, &train_data, alpha);
 
     // // Test predict_proba i predict na danych uczonych
     // let classes_prob: Vec<Vec<f64>> = nbc.predict_proba(&train_data, logarithmic);
@@ -474,9 +618,9 @@ fn main() {
     // println!("*   logharithmic      = true   *");
     // println!("********************************\n");
 
-    // let laplace_smoothing: bool = true;
+    // let alpha: f64 = 1.0;
     // let mut nbc = NBC::new(n_bins);
-    // nbc.fit(&train_classes, &train_data, laplace_smoothing);
+    // nbc.fit(&train_classes, &train_data, alpha);
 
     // // Test predict_proba i predict na danych uczonych
     // let classes_prob: Vec<Vec<f64>> = nbc.predict_proba(&train_data, logarithmic);
@@ -506,4 +650,5 @@ fn main() {
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception, clippy::bool_assert_comparison)]
 mod tests;
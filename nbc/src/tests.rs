@@ -57,4 +57,72 @@ mod tests {
         println!("Train data: {:?}", train_data);
         println!("Test data: {:?}", test_data);
     }
+
+    #[test]
+    fn test_gaussian_nb() {
+        let data = vec![
+            vec![1.0, 5.0],
+            vec![3.0, 5.0],
+            vec![10.0, 5.0],
+            vec![14.0, 5.0],
+        ];
+        let classes = vec![2, 2, 7, 7];
+
+        let mut gnb = GaussianNB::new(1e-9);
+        gnb.fit(&classes, &data);
+
+        assert_eq!(gnb.classes, vec![2, 7]);
+        assert_eq!(gnb.means, vec![vec![2.0, 5.0], vec![12.0, 5.0]]);
+        // Druga cecha jest stała, więc jej wariancja to samo wygładzenie (1e-9 razy największa wariancja)
+        let epsilon = 1e-9 * 27.5;
+        assert!((gnb.variances[0][0] - (1.0 + epsilon)).abs() < 1e-12);
+        assert!((gnb.variances[1][1] - epsilon).abs() < 1e-12);
+
+        assert_eq!(gnb.predict(&[vec![0.0, 5.0], vec![20.0, 5.0]]), vec![2, 7]);
+        for row in gnb.predict_proba(&data) {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        }
+
+        let (classes, data) = load_from_txt("wine.data", ",");
+        let mut gnb = GaussianNB::new(1e-9);
+        gnb.fit(&classes, &data);
+        let accuracy = return_correct_matches(&gnb.predict(&data), &classes) as f64 / classes.len() as f64;
+        assert!(accuracy > 0.95, "{}", accuracy);
+
+        // Same cechy stałe: wiarygodności są równe i decyduje prawdopodobieństwo a priori
+        let mut gnb = GaussianNB::new(1e-9);
+        gnb.fit(&[1, 2, 2], &[vec![5.0], vec![5.0], vec![5.0]]);
+        assert_eq!(gnb.predict(&[vec![5.0], vec![6.0]]), vec![2, 2]);
+        assert!(gnb.predict_proba(&[vec![5.0]])[0].iter().all(|p| p.is_finite()));
+    }
+
+    #[test]
+    fn test_nbc_distributions_sum_to_one() {
+        let (classes, mut data) = load_from_txt("wine.data", ",");
+        let n_bins = 5;
+        k_bins_discretizer(&mut data, n_bins);
+
+        for alpha in [0.0, 0.5, 1.0, 2.0] {
+            let mut nbc = NBC::new(n_bins);
+            nbc.fit(&classes, &data, alpha);
+            for (class, rows) in nbc.separate_classes.iter() {
+                for i in 0..data[0].len() {
+                    let sum: f64 = (0..n_bins).map(|bin| nbc.probabilities[&(*class, i, bin)]).sum();
+                    assert!((sum - 1.0).abs() < 1e-12, "alpha {}: {}", alpha, sum);
+
+                    // Przedział bez próbek dostaje alpha / (len + alpha * n_bins)
+                    for bin in (0..n_bins).filter(|&bin| rows.iter().all(|row| row[i] != bin as f64)) {
+                        let expected = alpha / (rows.len() as f64 + alpha * n_bins as f64);
+                        assert_eq!(nbc.probabilities[&(*class, i, bin)], expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "alpha musi być nieujemne")]
+    fn test_nbc_rejects_negative_alpha() {
+        NBC::new(3).fit(&[0], &[vec![1.0]], -0.5);
+    }
 }
\ No newline at end of file